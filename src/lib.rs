@@ -9,7 +9,23 @@
     unused_import_braces
 )]
 //! a library to parse markdown and convert it into sauron virtual node
-pub use markdown::{parse, parse_with_title};
+#[cfg(feature = "parse-html")]
+pub use markdown::SanitizePolicy;
+#[cfg(feature = "svgbob")]
+pub use markdown::SvgbobSettings;
+pub use markdown::{
+    parse, parse_with_footnotes, parse_with_front_matter_map, parse_with_options, parse_with_spans,
+    parse_with_title, parse_with_toc, render_toc, try_parse, try_parse_with_footnotes,
+    try_parse_with_front_matter_map, try_parse_with_options, try_parse_with_spans,
+    try_parse_with_title, try_parse_with_toc, Admonitions, CodeFenceInfo, CodeFenceProcessor,
+    DisallowedUrl, FootnoteReport, FrontMatter, FrontMatterFormat, FrontMatterValue, GithubSlugger,
+    IncrementalDocument, LinkInfo, MarkdownError, MarkdownOptions, MarkdownParser, NodeTransform,
+    Slugger, TableAlignment, TaskToggle, Toc, UrlPolicy,
+};
+#[cfg(any(feature = "front-matter-yaml", feature = "front-matter-toml"))]
+pub use markdown::{parse_with_front_matter, try_parse_with_front_matter};
+#[cfg(feature = "highlight")]
+pub use markdown::{HighlightTheme, TokenKind};
 /// reexport pulldown cmark
 pub use pulldown_cmark;
 pub use pulldown_cmark::Tag;
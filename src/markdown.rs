@@ -1,23 +1,544 @@
 use crate::sauron::{html, html::attributes, *};
-use pulldown_cmark::{CodeBlockKind, Event, Options, Parser, Tag};
+pub use admonition::Admonitions;
+pub use code_fence::CodeFenceInfo;
+pub use error::MarkdownError;
+pub use footnotes::FootnoteReport;
+use footnotes::Footnotes;
+pub use front_matter::{FrontMatter, FrontMatterFormat, FrontMatterValue};
+use heading::SlugRegistry;
+pub use heading::{GithubSlugger, Slugger};
+#[cfg(feature = "highlight")]
+pub use highlight::{HighlightTheme, TokenKind};
+pub use incremental::IncrementalDocument;
+pub use link::LinkInfo;
+pub use options::{MarkdownOptions, TableAlignment};
+pub use plugins::{CodeFenceProcessor, NodeTransform};
+use pulldown_cmark::{Alignment, CodeBlockKind, Event, Parser, Tag};
+#[cfg(feature = "parse-html")]
+pub use sanitizer::SanitizePolicy;
+use source_map::{LineIndex, LineOffsets};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::iter::Peekable;
+use std::ops::Range;
+#[cfg(feature = "svgbob")]
+pub use svgbob_processor::SvgbobSettings;
+pub use task_list::TaskToggle;
+pub use toc::{render_toc, Toc};
+use url_policy::UrlKind;
+pub use url_policy::{DisallowedUrl, UrlPolicy};
 
-pub(crate) struct MdParser<MSG> {
+mod admonition;
+mod block_key;
+mod code_fence;
+mod error;
+mod footnotes;
+mod front_matter;
+mod heading;
+#[cfg(feature = "highlight")]
+mod highlight;
+#[cfg(feature = "parse-html")]
+mod html_parser;
+mod incremental;
+mod link;
+mod math;
+#[cfg(feature = "mathml")]
+mod mathml;
+mod options;
+mod plugins;
+#[cfg(feature = "parse-html")]
+mod sanitizer;
+mod source_map;
+#[cfg(feature = "svgbob")]
+mod svgbob_processor;
+mod task_list;
+mod toc;
+mod url_policy;
+
+/// A markdown parser configured with options and plugins.
+///
+/// ```rust
+/// use sauron_markdown::{sauron::*, CodeFenceInfo, MarkdownParser};
+///
+/// let parser = MarkdownParser::<()>::new().add_code_fence_processor(
+///     "quote",
+///     |_info: &CodeFenceInfo, code: &str| Some(blockquote([], [text(code)])),
+/// );
+/// let nodes = parser.parse("```quote\nhello\n```");
+/// ```
+pub struct MarkdownParser<MSG> {
+    options: MarkdownOptions,
+    fence_processors: BTreeMap<String, Box<dyn CodeFenceProcessor<MSG>>>,
+    /// the languages of the processors registered by the options,
+    /// which are replaced when the options change
+    builtin_processors: BTreeSet<String>,
+    node_transforms: Vec<Box<dyn NodeTransform<MSG>>>,
+    slugger: Box<dyn Slugger>,
+    /// creates the `on_change` attribute of the task list checkboxes
+    task_toggle: Option<Box<dyn Fn(TaskToggle) -> Attribute<MSG>>>,
+    /// creates the `on_click` attribute of the internal links
+    link_click: Option<Box<dyn Fn(LinkInfo) -> Option<Attribute<MSG>>>>,
+    /// creates the `on_click` attribute of the copy buttons of the code blocks
+    code_copy: Option<Box<dyn Fn(String) -> Attribute<MSG>>>,
+}
+
+impl<MSG> fmt::Debug for MarkdownParser<MSG> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MarkdownParser")
+            .field("options", &self.options)
+            .field(
+                "fence_processors",
+                &self.fence_processors.keys().collect::<Vec<_>>(),
+            )
+            .field("node_transforms", &self.node_transforms.len())
+            .field("task_toggle", &self.task_toggle.is_some())
+            .field("link_click", &self.link_click.is_some())
+            .field("code_copy", &self.code_copy.is_some())
+            .finish()
+    }
+}
+
+impl<MSG> Default for MarkdownParser<MSG> {
+    fn default() -> Self {
+        Self::with_options(MarkdownOptions::default())
+    }
+}
+
+impl<MSG> MarkdownParser<MSG> {
+    /// create a parser with the default options and no plugins
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// create a parser with these options
+    pub fn with_options(options: MarkdownOptions) -> Self {
+        let parser = Self {
+            options,
+            fence_processors: BTreeMap::new(),
+            builtin_processors: BTreeSet::new(),
+            node_transforms: vec![],
+            slugger: Box::new(GithubSlugger),
+            task_toggle: None,
+            link_click: None,
+            code_copy: None,
+        };
+        parser.add_builtin_processors()
+    }
+
+    /// use these options,
+    /// the built-in processors are registered again using the new options,
+    /// the processors added with `add_code_fence_processor` are kept
+    pub fn options(mut self, options: MarkdownOptions) -> Self {
+        self.options = options;
+        self.add_builtin_processors()
+    }
+
+    /// register the processors enabled by the options and the cargo features
+    fn add_builtin_processors(mut self) -> Self {
+        for lang in std::mem::take(&mut self.builtin_processors) {
+            self.fence_processors.remove(&lang);
+        }
+        if self.options.math {
+            #[cfg(feature = "mathml")]
+            let processor = math::MathProcessor::new(self.options.mathml);
+            #[cfg(not(feature = "mathml"))]
+            let processor = math::MathProcessor::new();
+            self.add_builtin_processor("math", Box::new(processor));
+        }
+        #[cfg(feature = "svgbob")]
+        {
+            let processor = svgbob_processor::SvgbobProcessor::new(&self.options.svgbob);
+            self.add_builtin_processor("bob", Box::new(processor));
+        }
+        self
+    }
+
+    /// register the built-in processor unless a processor is already registered to the language
+    fn add_builtin_processor(&mut self, lang: &str, processor: Box<dyn CodeFenceProcessor<MSG>>) {
+        if !self.fence_processors.contains_key(lang) {
+            self.fence_processors.insert(lang.to_string(), processor);
+            self.builtin_processors.insert(lang.to_string());
+        }
+    }
+
+    /// register a processor for fenced code blocks of this language,
+    /// replacing the processor previously registered to it
+    pub fn add_code_fence_processor(
+        mut self,
+        lang: impl ToString,
+        processor: impl CodeFenceProcessor<MSG> + 'static,
+    ) -> Self {
+        self.builtin_processors.remove(&lang.to_string());
+        self.fence_processors
+            .insert(lang.to_string(), Box::new(processor));
+        self
+    }
+
+    /// add a transform to be applied to the assembled nodes,
+    /// transforms are applied in the order they are added
+    pub fn add_node_transform(mut self, transform: impl NodeTransform<MSG> + 'static) -> Self {
+        self.node_transforms.push(Box::new(transform));
+        self
+    }
+
+    /// use this slugger for creating the heading ids instead of the [`GithubSlugger`]
+    pub fn slugger(mut self, slugger: impl Slugger + 'static) -> Self {
+        self.slugger = Box::new(slugger);
+        self
+    }
+
+    /// render the task list checkboxes with an `on_change` handler,
+    /// which dispatches the message created from the toggled task.
+    /// The message is created while parsing, once for every checkbox.
+    /// Use [`TaskToggle::apply`] to update the markdown source.
+    pub fn on_task_toggle<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(TaskToggle) -> MSG + 'static,
+        MSG: Clone + 'static,
+    {
+        self.task_toggle = Some(Box::new(move |toggle: TaskToggle| {
+            let msg = callback(toggle);
+            on_change(move |_event| msg.clone())
+        }));
+        self
+    }
+
+    /// intercept the clicks on the internal links, such as relative urls and fragments.
+    /// When the callback returns a message, the link is rendered with an `on_click` handler
+    /// which prevents the default navigation and dispatches the message instead.
+    /// The external links are left as is.
+    pub fn on_link_click<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(LinkInfo) -> Option<MSG> + 'static,
+        MSG: Clone + 'static,
+    {
+        self.link_click = Some(Box::new(move |link: LinkInfo| {
+            callback(link).map(|msg| {
+                on_click(move |event| {
+                    event.prevent_default();
+                    msg.clone()
+                })
+            })
+        }));
+        self
+    }
+
+    /// wrap the fenced code blocks in a `div.code-container` together with a copy button,
+    /// which dispatches the message created from the source text of the code block when clicked.
+    /// The message is created while parsing, once for every code block.
+    /// The code blocks rendered by a [`CodeFenceProcessor`] also get a copy button.
+    pub fn on_code_copy<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(String) -> MSG + 'static,
+        MSG: Clone + 'static,
+    {
+        self.code_copy = Some(Box::new(move |code: String| {
+            let msg = callback(code);
+            on_click(move |_event| msg.clone())
+        }));
+        self
+    }
+
+    /// parse the markdown and return the nodes
+    ///
+    /// If the markdown can not be converted, the source is displayed as is
+    /// in a `pre` element instead of panicking.
+    pub fn parse(&self, src: &str) -> Vec<Node<MSG>> {
+        self.try_parse(src).unwrap_or_else(|e| fallback(src, e))
+    }
+
+    /// parse the markdown and return the nodes, or the error encountered
+    pub fn try_parse(&self, src: &str) -> Result<Vec<Node<MSG>>, MarkdownError> {
+        let document = self.try_parse_document(MdParser::new(self), src)?;
+        Ok(document.nodes)
+    }
+
+    /// parse the markdown and return the table of contents and the nodes,
+    /// the headings are given ids even if `heading_ids` is not enabled
+    pub fn parse_with_toc(&self, src: &str) -> (Vec<Toc>, Vec<Node<MSG>>) {
+        self.try_parse_with_toc(src)
+            .unwrap_or_else(|e| (vec![], fallback(src, e)))
+    }
+
+    /// parse the markdown and return the table of contents and the nodes,
+    /// or the error encountered
+    pub fn try_parse_with_toc(
+        &self,
+        src: &str,
+    ) -> Result<(Vec<Toc>, Vec<Node<MSG>>), MarkdownError> {
+        let mut md_parser = MdParser::new(self);
+        md_parser.heading_ids = true;
+        let document = self.try_parse_document(md_parser, src)?;
+        Ok((document.toc, document.nodes))
+    }
+
+    /// parse the markdown and return the nodes together with the byte range in the source
+    /// of each of the top level nodes.
+    ///
+    /// The range is `None` for nodes which are not from the source, such as the footnotes section.
+    pub fn parse_with_spans(&self, src: &str) -> (Vec<Node<MSG>>, Vec<Option<Range<usize>>>) {
+        self.try_parse_with_spans(src)
+            .unwrap_or_else(|e| (fallback(src, e), vec![Some(0..src.len())]))
+    }
+
+    /// parse the markdown and return the nodes together with the byte range in the source
+    /// of each of the top level nodes, or the error encountered
+    pub fn try_parse_with_spans(
+        &self,
+        src: &str,
+    ) -> Result<(Vec<Node<MSG>>, Vec<Option<Range<usize>>>), MarkdownError> {
+        let document = self.try_parse_document(MdParser::new(self), src)?;
+        Ok((document.nodes, document.spans))
+    }
+
+    /// parse the markdown and return the nodes together with the footnotes
+    /// which are referenced but not defined, or defined but never referenced
+    pub fn parse_with_footnotes(&self, src: &str) -> (Vec<Node<MSG>>, FootnoteReport) {
+        self.try_parse_with_footnotes(src)
+            .unwrap_or_else(|e| (fallback(src, e), FootnoteReport::default()))
+    }
+
+    /// parse the markdown and return the nodes together with the footnotes
+    /// which are not linked, or the error encountered
+    pub fn try_parse_with_footnotes(
+        &self,
+        src: &str,
+    ) -> Result<(Vec<Node<MSG>>, FootnoteReport), MarkdownError> {
+        let document = self.try_parse_document(MdParser::new(self), src)?;
+        Ok((document.nodes, document.footnotes))
+    }
+
+    /// parse the markdown and return the front matter and the nodes
+    pub fn parse_with_front_matter_map(&self, src: &str) -> (Option<FrontMatter>, Vec<Node<MSG>>) {
+        self.try_parse_with_front_matter_map(src)
+            .unwrap_or_else(|e| (None, fallback(src, e)))
+    }
+
+    /// parse the markdown and return the front matter and the nodes,
+    /// or the error encountered
+    pub fn try_parse_with_front_matter_map(
+        &self,
+        src: &str,
+    ) -> Result<(Option<FrontMatter>, Vec<Node<MSG>>), MarkdownError> {
+        let document = self.try_parse_document(MdParser::new(self), src)?;
+        Ok((document.front_matter, document.nodes))
+    }
+
+    /// parse the markdown and return the front matter deserialized into `T` and the nodes.
+    ///
+    /// If the front matter can not be deserialized, the error is logged and `None` is returned.
+    #[cfg(any(feature = "front-matter-yaml", feature = "front-matter-toml"))]
+    pub fn parse_with_front_matter<T>(&self, src: &str) -> (Option<T>, Vec<Node<MSG>>)
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let (front_matter, nodes) = self.parse_with_front_matter_map(src);
+        let front_matter = front_matter.and_then(|front_matter| {
+            front_matter
+                .deserialize()
+                .map_err(|e| log::error!("{}", e))
+                .ok()
+        });
+        (front_matter, nodes)
+    }
+
+    /// parse the markdown and return the front matter deserialized into `T` and the nodes,
+    /// or the error encountered
+    #[cfg(any(feature = "front-matter-yaml", feature = "front-matter-toml"))]
+    pub fn try_parse_with_front_matter<T>(
+        &self,
+        src: &str,
+    ) -> Result<(Option<T>, Vec<Node<MSG>>), MarkdownError>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let (front_matter, nodes) = self.try_parse_with_front_matter_map(src)?;
+        let front_matter = front_matter
+            .map(|front_matter| front_matter.deserialize())
+            .transpose()?;
+        Ok((front_matter, nodes))
+    }
+
+    /// apply the node transforms to the nodes
+    fn apply_transforms(&self, nodes: Vec<Node<MSG>>) -> Vec<Node<MSG>> {
+        if self.node_transforms.is_empty() {
+            nodes
+        } else {
+            plugins::transform_nodes(nodes, &self.node_transforms)
+        }
+    }
+
+    /// strip the front matter, then convert the markdown and apply the node transforms
+    fn try_parse_document(
+        &self,
+        md_parser: MdParser<'_, MSG>,
+        src: &str,
+    ) -> Result<Document<MSG>, MarkdownError> {
+        let (front_matter, body) = if self.options.front_matter {
+            FrontMatter::extract(src)
+        } else {
+            (None, src)
+        };
+        let body_start = src.len() - body.len();
+        let (toc, nodes, spans, footnotes) = md_parser.parse(src, body_start)?;
+        // the transforms are applied to each top level node to keep them aligned with the spans
+        let (mut nodes, spans): (Vec<_>, Vec<_>) = if self.node_transforms.is_empty() {
+            (nodes, spans)
+        } else {
+            nodes
+                .into_iter()
+                .zip(spans)
+                .filter_map(|(node, span)| {
+                    plugins::transform_node(node, &self.node_transforms).map(|node| (node, span))
+                })
+                .unzip()
+        };
+        if self.options.block_keys {
+            block_key::set_span_keys(src, &mut nodes, &spans);
+        }
+        Ok(Document {
+            front_matter,
+            toc,
+            nodes,
+            spans,
+            footnotes,
+        })
+    }
+
+    /// parse the markdown and return the first encountered h1 text and the nodes
+    ///
+    /// The title is the text of all the descendants of the h1,
+    /// including the code spans and the text of the links.
+    pub fn parse_with_title(&self, src: &str) -> (Option<String>, Vec<Node<MSG>>) {
+        self.try_parse_with_title(src)
+            .unwrap_or_else(|e| (None, fallback(src, e)))
+    }
+
+    /// parse the markdown and return the first encountered h1 text and the nodes,
+    /// or the error encountered
+    pub fn try_parse_with_title(
+        &self,
+        src: &str,
+    ) -> Result<(Option<String>, Vec<Node<MSG>>), MarkdownError> {
+        let Document {
+            front_matter,
+            mut nodes,
+            ..
+        } = self.try_parse_document(MdParser::new(self), src)?;
+        let strip = self.options.strip_title;
+        let mut title = take_heading(&mut nodes, "h1", strip).map(|title| self.clean_title(&title));
+        if title.is_none() && self.options.title_fallback_front_matter {
+            title = front_matter
+                .as_ref()
+                .and_then(FrontMatter::title)
+                .map(ToString::to_string);
+        }
+        if title.is_none() && self.options.title_fallback_h2 {
+            title = take_heading(&mut nodes, "h2", strip).map(|title| self.clean_title(&title));
+        }
+        Ok((title, nodes))
+    }
+
+    /// remove the permalink text which is included in the heading text
+    fn clean_title(&self, title: &str) -> String {
+        let title = title.trim();
+        let permalink = self
+            .options
+            .heading_permalink
+            .as_deref()
+            .filter(|_| self.options.heading_ids || self.options.toc_placeholder);
+        match permalink.and_then(|symbol| title.strip_suffix(symbol)) {
+            Some(title) => title.trim_end().to_string(),
+            None => title.to_string(),
+        }
+    }
+}
+
+/// the result of parsing the markdown
+struct Document<MSG> {
+    front_matter: Option<FrontMatter>,
+    toc: Vec<Toc>,
+    nodes: Vec<Node<MSG>>,
+    /// the byte range in the source of each of the top level nodes
+    spans: Vec<Option<Range<usize>>>,
+    footnotes: FootnoteReport,
+}
+
+pub(crate) struct MdParser<'a, MSG> {
+    parser: &'a MarkdownParser<MSG>,
+    options: &'a MarkdownOptions,
     spine: Vec<Node<MSG>>,
     nodes: Vec<Node<MSG>>,
+    /// the info and the accumulated text of the code block being parsed
+    code_block: Option<(CodeFenceInfo, String)>,
+    /// whether the code block being parsed is fenced, only fenced code blocks get a copy button.
+    /// The code blocks created from the `$` math are not fenced code blocks
+    code_block_fenced: bool,
+    /// whether the code block being parsed is created from the `$` math
+    code_block_math: bool,
+    /// the column alignments of the table being parsed
+    table_alignments: Vec<Alignment>,
+    /// the index of the table cell being parsed, used for looking up its alignment
+    table_cell_index: usize,
+    in_table_head: bool,
+    /// whether the `tbody` of the table being parsed is opened
+    in_table_body: bool,
+    footnotes: Footnotes<MSG>,
+    /// consecutive html events are accumulated and parsed together
+    html_buffer: String,
+    /// the spine length at the time an unclosed html element was pushed to the spine
+    open_html: Vec<usize>,
+    /// whether the headings are given ids
+    heading_ids: bool,
+    slugs: SlugRegistry,
+    /// the headings in the order they appear in the document
+    headings: Vec<Toc>,
+    /// the byte range in the source of the event being processed
+    range: Range<usize>,
+    /// the byte range in the source of the html in the buffer
+    html_range: Option<Range<usize>>,
+    /// where the top level node being parsed starts in the source
+    block_start: Option<usize>,
+    /// the source range of each of the top level nodes
+    spans: Vec<Option<Range<usize>>>,
+    /// used for the `data-sourcepos` attribute, when `source_positions` is enabled
+    lines: Option<LineIndex>,
+    /// the number of task list items encountered
+    task_index: usize,
 }
-impl<MSG> MdParser<MSG> {
-    fn new() -> Self {
+impl<'a, MSG> MdParser<'a, MSG> {
+    fn new(parser: &'a MarkdownParser<MSG>) -> Self {
         Self {
+            parser,
+            options: &parser.options,
             spine: vec![],
             nodes: vec![],
+            code_block: None,
+            code_block_fenced: false,
+            code_block_math: false,
+            table_alignments: vec![],
+            table_cell_index: 0,
+            in_table_head: false,
+            in_table_body: false,
+            footnotes: Footnotes::new(),
+            html_buffer: String::new(),
+            open_html: vec![],
+            heading_ids: parser.options.heading_ids || parser.options.toc_placeholder,
+            slugs: SlugRegistry::new(),
+            headings: vec![],
+            range: 0..0,
+            html_range: None,
+            block_start: None,
+            spans: vec![],
+            lines: None,
+            task_index: 0,
         }
     }
-    fn add_child(&mut self, child: Node<MSG>) {
+    fn add_child(&mut self, child: Node<MSG>) -> Result<(), MarkdownError> {
         log::info!("adding child: {:?}", child);
-        assert!(!self.spine.is_empty());
-        if let Some(last) = self.spine.last_mut() {
-            last.add_children([child]).expect("add children");
-        }
+        let last = self.spine.last_mut().ok_or(MarkdownError::SpineUnderflow)?;
+        last.add_children([child])
+            .map_err(|e| MarkdownError::AttachNode(e.to_string()))
     }
 
     /// push node to the spine
@@ -27,133 +548,882 @@ impl<MSG> MdParser<MSG> {
 
     /// push as a new node
     fn push_to_nodes(&mut self, node: Node<MSG>) {
+        let end = self.range.end;
+        let start = match self.block_start.take() {
+            Some(start) => start.min(self.range.start),
+            None => self.range.start,
+        };
         self.nodes.push(node);
+        self.spans.push(Some(start..end));
+    }
+
+    /// the `data-sourcepos` attribute of the range being processed
+    fn sourcepos_attribute(&self) -> Option<Attribute<MSG>> {
+        self.lines
+            .as_ref()
+            .map(|lines| attr("data-sourcepos", lines.sourcepos(&self.range)))
+    }
+
+    /// add the `data-sourcepos` attribute to the node, if `source_positions` is enabled
+    fn add_sourcepos(&self, node: &mut Node<MSG>) {
+        add_attributes(node, self.sourcepos_attribute());
+    }
+
+    /// the click handler of the internal links,
+    /// or the attributes for opening the external links in a new tab
+    fn link_attributes(&self, link: LinkInfo) -> Vec<Attribute<MSG>> {
+        if link.is_internal {
+            self.parser
+                .link_click
+                .as_ref()
+                .and_then(|link_click| link_click(link))
+                .into_iter()
+                .collect()
+        } else if self.options.external_links_new_tab {
+            vec![attr("target", "_blank"), attr("rel", "noopener")]
+        } else {
+            vec![]
+        }
+    }
+
+    /// add the `data-sourcepos` attribute to the node on top of the spine
+    fn add_sourcepos_to_spine(&mut self) {
+        if let Some(mut node) = self.spine.pop() {
+            self.add_sourcepos(&mut node);
+            self.spine.push(node);
+        }
+    }
+
+    /// add the node to the top of the spine,
+    /// or to the top level nodes if there is no open element
+    fn attach(&mut self, node: Node<MSG>) -> Result<(), MarkdownError> {
+        if self.spine.is_empty() {
+            self.push_to_nodes(node);
+            Ok(())
+        } else {
+            self.add_child(node)
+        }
+    }
+
+    /// pop the top of the spine and attach it to its parent,
+    /// or to the top level nodes if there is no more parent
+    fn close_tag(&mut self) -> Result<(), MarkdownError> {
+        let top = self.spine.pop().ok_or(MarkdownError::SpineUnderflow)?;
+        self.attach(top)
+    }
+
+    /// assemble the buffered code block into `<pre><code>`,
+    /// or the node returned by the processor registered to its language
+    fn end_code_block(&mut self) -> Result<(), MarkdownError> {
+        // the `pre` pushed at the start of the code block is replaced with the assembled node
+        self.spine.pop().ok_or(MarkdownError::SpineUnderflow)?;
+        if let Some((info, content)) = self.code_block.take() {
+            let processed = info
+                .lang
+                .as_ref()
+                .and_then(|lang| self.parser.fence_processors.get(lang))
+                .and_then(|processor| processor.process(&info, &content));
+            let node = match processed {
+                Some(node) => node,
+                // the math is inside of a paragraph, where a `pre` can not be
+                None if self.code_block_math => math::tex_element(&info, &content),
+                None => code_block(&info, &content, self.options),
+            };
+            let mut node = match &self.parser.code_copy {
+                Some(code_copy) if self.code_block_fenced => {
+                    copy_container(node, code_copy(content))
+                }
+                _ => node,
+            };
+            self.add_sourcepos(&mut node);
+            self.attach(node)
+        } else {
+            Err(MarkdownError::SpineUnderflow)
+        }
+    }
+
+    /// create the element of the tag, the table cells are aligned by the alignment of their column
+    fn make_tag(&self, tag: &Tag) -> Result<Node<MSG>, MarkdownError> {
+        let node = match tag {
+            Tag::Paragraph => p([], []),
+            Tag::Heading(n) => heading(*n, vec![])?,
+            Tag::BlockQuote => blockquote([], []),
+            // the content of code blocks are buffered and assembled in `end_code_block`
+            Tag::CodeBlock(_kind) => pre([], []),
+            Tag::List(None) => ul([], []),
+            Tag::List(Some(1)) => ol([], []),
+            Tag::List(Some(start)) => ol([attr("start", start.to_string())], []),
+            Tag::Item => li([], []),
+            Tag::Table(_alignment) => table([], []),
+            Tag::TableHead => thead([], []),
+            Tag::TableRow => tr([], []),
+            Tag::TableCell => {
+                let alignment = self
+                    .table_alignments
+                    .get(self.table_cell_index)
+                    .copied()
+                    .unwrap_or(Alignment::None);
+                let attributes = [alignment_attribute(alignment, self.options.table_alignment)];
+                if self.in_table_head {
+                    th(attributes, [])
+                } else {
+                    td(attributes, [])
+                }
+            }
+            Tag::Emphasis => em([], []),
+            Tag::Strong => strong([], []),
+            Tag::Link(_type, href_, title_) => a(
+                [
+                    url_attribute(self.options, href_, UrlKind::Link, href),
+                    attributes::title(title_.to_string()),
+                ],
+                [],
+            ),
+            Tag::Image(_type, src_, title_) => img(
+                [
+                    url_attribute(self.options, src_, UrlKind::Image, src),
+                    attributes::title(title_.to_string()),
+                ],
+                [],
+            ),
+            Tag::Strikethrough => html::s([], []),
+            Tag::FootnoteDefinition(name) => Footnotes::definition_container(name),
+        };
+        Ok(node)
+    }
+
+    fn start_tag(&mut self, tag: Tag) -> Result<(), MarkdownError> {
+        // code blocks are assembled at the end of the block
+        let is_block = is_block_tag(&tag) && !matches!(tag, Tag::CodeBlock(_));
+        match &tag {
+            Tag::CodeBlock(kind) => {
+                self.code_block = Some((CodeFenceInfo::from_kind(kind), String::new()));
+                self.code_block_math =
+                    matches!(kind, CodeBlockKind::Fenced(info) if math::is_math_span(info));
+                self.code_block_fenced =
+                    matches!(kind, CodeBlockKind::Fenced(_)) && !self.code_block_math;
+            }
+            Tag::Table(alignments) => {
+                self.table_alignments = alignments.clone();
+            }
+            Tag::TableHead => {
+                self.in_table_head = true;
+                self.table_cell_index = 0;
+            }
+            Tag::TableRow => {
+                self.table_cell_index = 0;
+                // the `tbody` is only opened for the first row after the table head,
+                // so a table with only a head has no empty `tbody`
+                if !self.in_table_body {
+                    self.in_table_body = true;
+                    self.push_to_spine(tbody([], []));
+                }
+            }
+            _ => (),
+        }
+        let mut node = self.make_tag(&tag)?;
+        if let Tag::Link(_, url, title) = &tag {
+            // the handlers are only added to the links whose url is allowed by the url policy,
+            // the removed and replaced urls are not reported to the link handlers
+            if is_url_allowed(self.options, url, UrlKind::Link) {
+                add_attributes(&mut node, self.link_attributes(LinkInfo::new(url, title)));
+            }
+        }
+        self.push_to_spine(node);
+        if let Tag::TableHead = tag {
+            // the cells of the table head are not wrapped in a `TableRow`
+            self.push_to_spine(tr([], []));
+        }
+        if is_block {
+            self.add_sourcepos_to_spine();
+        }
+        Ok(())
+    }
+
+    /// add the html to the buffer, the html is processed on the next non-html event
+    fn push_html(&mut self, html: &str) -> Result<(), MarkdownError> {
+        if self.options.allow_html {
+            self.html_buffer.push_str(html);
+            self.html_range = Some(match self.html_range.take() {
+                Some(html_range) => html_range.start..self.range.end,
+                None => self.range.clone(),
+            });
+            Ok(())
+        } else {
+            self.attach(text(html))
+        }
+    }
+
+    /// process the html accumulated in the buffer
+    fn flush_html(&mut self) -> Result<(), MarkdownError> {
+        if self.html_buffer.is_empty() {
+            Ok(())
+        } else {
+            let html = std::mem::take(&mut self.html_buffer);
+            let range = match self.html_range.take() {
+                Some(html_range) => std::mem::replace(&mut self.range, html_range),
+                None => self.range.clone(),
+            };
+            let result = self.process_html(&html);
+            self.range = range;
+            result
+        }
+    }
+
+    /// convert the html into nodes,
+    /// an unclosed html tag is pushed to the spine so the markdown that follows
+    /// becomes its children until the matching closing tag.
+    #[cfg(feature = "parse-html")]
+    fn process_html(&mut self, html: &str) -> Result<(), MarkdownError> {
+        let lone_tag = html_parser::lone_tag(html);
+        if let Some(html_parser::LoneTag::Close(tag)) = lone_tag {
+            return self.close_html(tag);
+        }
+        match html_parser::parse_with_policy(html, self.options.sanitize.as_ref()) {
+            Ok(Some(node)) => {
+                // the open tag could have been removed by the sanitizer
+                let is_open_tag = matches!(lone_tag, Some(html_parser::LoneTag::Open(_)));
+                if is_open_tag && node.tag().is_some() {
+                    self.push_to_spine(node);
+                    self.open_html.push(self.spine.len());
+                    Ok(())
+                } else {
+                    self.attach(node)
+                }
+            }
+            Ok(None) => Ok(()),
+            Err(e) => {
+                log::warn!("unable to parse html: {}", e);
+                // the html which can not be sanitized is escaped instead
+                if self.options.sanitize.is_some() {
+                    self.attach(text(html))
+                } else {
+                    self.attach(raw_html(html))
+                }
+            }
+        }
     }
 
-    fn parse(mut self, src: &str) -> Vec<Node<MSG>> {
-        for ev in Parser::new_ext(src, Options::all()) {
+    #[cfg(not(feature = "parse-html"))]
+    fn process_html(&mut self, html: &str) -> Result<(), MarkdownError> {
+        self.attach(raw_html(html))
+    }
+
+    /// close the html element on top of the spine if it matches the closing tag
+    #[cfg(feature = "parse-html")]
+    fn close_html(&mut self, tag: &str) -> Result<(), MarkdownError> {
+        let is_open = self.open_html.last() == Some(&self.spine.len())
+            && self
+                .spine
+                .last()
+                .and_then(|node| node.tag())
+                .map(|t| t.eq_ignore_ascii_case(tag))
+                .unwrap_or(false);
+        if is_open {
+            self.open_html.pop();
+            self.close_tag()
+        } else {
+            log::warn!("closing html tag without a matching opening tag: {}", tag);
+            Ok(())
+        }
+    }
+
+    /// close the html elements which are left open inside the markdown element
+    fn close_open_html(&mut self) -> Result<(), MarkdownError> {
+        while self.open_html.last() == Some(&self.spine.len()) {
+            self.open_html.pop();
+            self.close_tag()?;
+        }
+        Ok(())
+    }
+
+    fn end_tag(&mut self, tag: Tag) -> Result<(), MarkdownError> {
+        self.close_open_html()?;
+        match tag {
+            Tag::CodeBlock(_) => self.end_code_block(),
+            Tag::TableHead => {
+                // close the `tr` and `thead`
+                self.close_tag()?;
+                self.in_table_head = false;
+                self.close_tag()
+            }
+            Tag::TableCell => {
+                self.table_cell_index += 1;
+                self.close_tag()
+            }
+            Tag::Table(_) => {
+                // close the `tbody` and `table`
+                if self.in_table_body {
+                    self.in_table_body = false;
+                    self.close_tag()?;
+                }
+                self.close_tag()
+            }
+            Tag::FootnoteDefinition(name) => {
+                // definitions are collected into the footnote section at the end
+                let definition = self.spine.pop().ok_or(MarkdownError::SpineUnderflow)?;
+                self.footnotes.define(&name, definition);
+                Ok(())
+            }
+            _ => self.close_tag(),
+        }
+    }
+
+    fn process_event(&mut self, ev: Event) -> Result<(), MarkdownError> {
+        if !matches!(ev, Event::Html(_)) {
+            self.flush_html()?;
+        }
+        match ev {
+            Event::Start(tag) => self.start_tag(tag)?,
+            Event::End(tag) => self.end_tag(tag)?,
+            Event::Text(text_) => {
+                if let Some((_info, content)) = self.code_block.as_mut() {
+                    content.push_str(&text_);
+                } else {
+                    self.add_child(text(text_))?
+                }
+            }
+            Event::SoftBreak => {
+                if self.options.soft_break_as_line_break {
+                    self.add_child(br([], []))?
+                } else {
+                    self.add_child(text("\n"))?
+                }
+            }
+            Event::HardBreak => self.add_child(br([], []))?,
+            Event::Html(html) => self.push_html(&html)?,
+            Event::Code(content) => self.add_child(code([], [text(content)]))?,
+            Event::Rule => {
+                // the rule is a top level element, unless it is inside a container
+                // such as a blockquote or an admonition
+                let mut rule = hr([], []);
+                self.add_sourcepos(&mut rule);
+                self.attach(rule)?;
+            }
+            Event::FootnoteReference(name) => {
+                let reference = self.footnotes.reference(&name);
+                self.add_child(reference)?
+            }
+            Event::TaskListMarker(value) => {
+                let mut attributes = vec![r#type("checkbox"), checked(value)];
+                if let Some(task_toggle) = &self.parser.task_toggle {
+                    attributes.push(task_toggle(TaskToggle {
+                        index: self.task_index,
+                        range: self.range.clone(),
+                        checked: !value,
+                    }));
+                }
+                self.task_index += 1;
+                self.add_child(input(attributes, []))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// process the events of a heading, from its `Start` to its `End`,
+    /// adding the id and the permalink to the heading
+    fn process_heading(
+        &mut self,
+        mut events: Vec<Event>,
+        range: Range<usize>,
+    ) -> Result<(), MarkdownError> {
+        let options = self.options;
+        let custom_id = heading::take_custom_id(&mut events);
+        let heading_text = heading::heading_text(&events);
+        let slug = match custom_id {
+            Some(custom_id) => custom_id,
+            None => self.parser.slugger.slug(&heading_text),
+        };
+        let slug = self.slugs.unique(slug);
+        for ev in events {
             match ev {
-                Event::Start(tag) => {
-                    self.push_to_spine(make_tag(&tag));
-                }
-                Event::End(_tag) => {
-                    assert!(!self.spine.is_empty());
-                    //TODO: there is a big code smell here
-                    let len = self.spine.len();
-                    let top = self.spine.pop().unwrap();
-                    if len == 1 {
-                        self.push_to_nodes(top);
-                    } else {
-                        // TODO: code smell
-                        // we are adding the top children if there are more than 2 elemen in the
-                        // spine
-                        self.spine[len - 2]
-                            .add_children([top])
-                            .expect("add children");
+                Event::Start(Tag::Heading(level)) => {
+                    self.headings
+                        .push(Toc::new(level, heading_text.clone(), slug.clone()));
+                    self.flush_html()?;
+                    self.range = range.clone();
+                    self.push_to_spine(heading(level, vec![id(slug.clone())])?);
+                    self.add_sourcepos_to_spine();
+                }
+                Event::End(Tag::Heading(level)) => {
+                    if let Some(symbol) = &options.heading_permalink {
+                        self.flush_html()?;
+                        let permalink = a(
+                            [class("anchor"), href(format!("#{}", slug))],
+                            [text(symbol)],
+                        );
+                        self.add_child(permalink)?;
                     }
+                    self.range = range.clone();
+                    self.process_event(Event::End(Tag::Heading(level)))?;
                 }
-                Event::Text(text_) => self.add_child(text(text_)),
-                Event::SoftBreak => self.add_child(text("\n")),
-                Event::HardBreak => self.add_child(br([], [])),
-                Event::Html(html) => {
-                    if self.spine.is_empty() {
-                        self.push_to_nodes(raw_html(&html));
-                    } else {
-                        self.add_child(raw_html(&html))
+                ev => self.process_event(ev)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// replace the `[TOC]` paragraph with the table of contents
+    fn replace_toc_placeholder(&mut self, toc: &[Toc]) {
+        let placeholder = self.nodes.iter().position(|node| {
+            node.tag() == Some(&"p")
+                && node
+                    .children()
+                    .iter()
+                    .all(|child| child.as_text().is_some())
+                && heading::node_text(node).trim() == "[TOC]"
+        });
+        if let Some(index) = placeholder {
+            self.nodes[index] = render_toc(toc);
+        }
+    }
+
+    /// set the source range of the event about to be processed
+    fn set_range(&mut self, src: &str, range: Range<usize>) {
+        let range = source_map::trim_range(src, range);
+        if self.spine.is_empty() && self.html_buffer.is_empty() {
+            self.block_start = Some(range.start);
+        }
+        self.range = range;
+    }
+
+    /// process the events of the markdown body, the `offsets` maps the ranges of the events
+    /// into the source. The body is either a slice of the source or the dedented content
+    /// of a `!!!` admonition.
+    fn process_markdown(
+        &mut self,
+        src: &str,
+        body: &str,
+        offsets: &LineOffsets,
+    ) -> Result<(), MarkdownError> {
+        let events = Parser::new_ext(body, self.options.pulldown_options()).into_offset_iter();
+        let events: Box<dyn Iterator<Item = _>> = if self.options.math {
+            Box::new(math::MathEvents::new(body, events))
+        } else {
+            Box::new(events)
+        };
+        let mut events = events.peekable();
+        // the `!!!` admonitions are only recognized at the top level of the body
+        let mut depth = 0;
+        let mut definitions = None;
+        while let Some((ev, range)) = events.next() {
+            self.set_range(src, offsets.source_range(range.clone()));
+            let options = self.options;
+            let admonitions = options.admonitions.as_ref();
+            let fence_admonition = |start| {
+                let admonitions = admonitions?;
+                let (header, content) = admonitions.fence_block(body, start)?;
+                Some((admonitions.container(&header)?, content))
+            };
+            if self.heading_ids && matches!(ev, Event::Start(Tag::Heading(_))) {
+                let mut heading_events = vec![ev];
+                for (ev, _range) in events.by_ref() {
+                    let is_end = matches!(ev, Event::End(Tag::Heading(_)));
+                    heading_events.push(ev);
+                    if is_end {
+                        break;
                     }
                 }
-                Event::Code(content) => {
-                    self.add_child(code([],[text(content)]))
+                let range = self.range.clone();
+                self.process_heading(heading_events, range)?;
+            } else if let Some(container) = admonitions
+                .filter(|_| matches!(ev, Event::Start(Tag::BlockQuote)))
+                .and_then(|admonitions| admonitions.github(&body[range.start..]))
+            {
+                self.flush_html()?;
+                self.push_to_spine(container);
+                self.add_sourcepos_to_spine();
+                depth += 1;
+                let marker_end = body[range.start..]
+                    .find('\n')
+                    .map_or(body.len(), |end| range.start + end);
+                if let Some(paragraph) = skip_admonition_marker(&mut events, marker_end) {
+                    self.set_range(src, offsets.source_range(paragraph));
+                    self.process_event(Event::Start(Tag::Paragraph))?;
                 }
-                Event::Rule => {
-                    // <hr> rule is top level element
-                    self.push_to_nodes(hr([], []));
+            } else if let Some((container, content)) = Some(range.start)
+                .filter(|_| depth == 0 && matches!(ev, Event::Start(Tag::Paragraph)))
+                .and_then(fence_admonition)
+            {
+                // the content is parsed separately, since the indented content
+                // would otherwise be parsed as code blocks
+                let mut end = content.end;
+                let mut skipped_depth = 1;
+                while let Some((ev, range)) = events.peek() {
+                    if skipped_depth == 0 && range.start >= end {
+                        break;
+                    }
+                    match ev {
+                        Event::Start(_) => skipped_depth += 1,
+                        Event::End(_) => skipped_depth -= 1,
+                        _ => (),
+                    }
+                    end = end.max(range.end);
+                    events.next();
                 }
-                Event::FootnoteReference(name) => self.add_child(a([href(name.to_string())], [])),
-                Event::TaskListMarker(ref value) => {
-                    self.add_child(input([r#type("checkbox"), checked(*value)], []));
+                let admonition_range = offsets.source_range(range.start..end);
+                self.set_range(src, admonition_range.clone());
+                self.flush_html()?;
+                self.push_to_spine(container);
+                self.add_sourcepos_to_spine();
+                let (mut content, content_offsets) =
+                    admonition::dedent(body, content.start..end, offsets);
+                // the link reference definitions of the body are not a part of the content
+                let definitions = definitions.get_or_insert_with(|| {
+                    admonition::definitions(body, self.options.pulldown_options())
+                });
+                content.push_str("\n\n");
+                content.push_str(definitions);
+                self.process_markdown(src, &content, &content_offsets)?;
+                self.flush_html()?;
+                self.set_range(src, admonition_range);
+                self.close_tag()?;
+            } else {
+                match ev {
+                    Event::Start(_) => depth += 1,
+                    Event::End(_) => depth -= 1,
+                    _ => (),
                 }
+                self.process_event(ev)?;
             }
         }
-        self.nodes
+        Ok(())
+    }
+
+    /// parse the markdown which starts at `body_start` of the source,
+    /// the source ranges are relative to the whole source
+    #[allow(clippy::type_complexity)]
+    fn parse(
+        mut self,
+        src: &str,
+        body_start: usize,
+    ) -> Result<
+        (
+            Vec<Toc>,
+            Vec<Node<MSG>>,
+            Vec<Option<Range<usize>>>,
+            FootnoteReport,
+        ),
+        MarkdownError,
+    > {
+        if self.options.source_positions {
+            self.lines = Some(LineIndex::new(src));
+        }
+        self.process_markdown(src, &src[body_start..], &LineOffsets::new(body_start))?;
+        self.flush_html()?;
+        // close any element that was left open
+        self.open_html.clear();
+        while !self.spine.is_empty() {
+            self.close_tag()?;
+        }
+        let toc = toc::nest(std::mem::take(&mut self.headings));
+        if self.options.toc_placeholder {
+            self.replace_toc_placeholder(&toc);
+        }
+        let footnotes = std::mem::replace(&mut self.footnotes, Footnotes::new());
+        let (section, footnotes) = footnotes.into_section()?;
+        if let Some(section) = section {
+            self.nodes.push(section);
+            self.spans.push(None);
+        }
+        Ok((toc, self.nodes, self.spans, footnotes))
     }
 }
 
-fn make_tag<MSG>(t: &Tag) -> Node<MSG> {
-    match t {
-        Tag::Paragraph => p([], []),
-        Tag::Heading(n) => {
-            assert!(*n > 0);
-            assert!(*n < 7);
-            match n {
-                1 => h1([], []),
-                2 => h2([], []),
-                3 => h3([], []),
-                4 => h4([], []),
-                5 => h5([], []),
-                6 => h6([], []),
-                _ => unreachable!(),
+/// add the attributes to the node, text nodes are left as is
+fn add_attributes<MSG>(node: &mut Node<MSG>, attributes: impl IntoIterator<Item = Attribute<MSG>>) {
+    if let Node::Element(element) = node {
+        element.attrs.extend(attributes);
+    }
+}
+
+/// skip the `[!NOTE]` marker line of the GitHub style admonition which ends at `marker_end`,
+/// returning the range of the paragraph if it has more content after the marker
+fn skip_admonition_marker<'e, I>(
+    events: &mut Peekable<I>,
+    marker_end: usize,
+) -> Option<Range<usize>>
+where
+    I: Iterator<Item = (Event<'e>, Range<usize>)>,
+{
+    let paragraph = match events.peek() {
+        Some((Event::Start(Tag::Paragraph), range)) if range.start < marker_end => range.clone(),
+        _ => return None,
+    };
+    events.next();
+    loop {
+        match events.peek() {
+            Some((Event::End(Tag::Paragraph), _)) => {
+                events.next();
+                return None;
+            }
+            Some((Event::SoftBreak, _)) | Some((Event::HardBreak, _)) => {
+                events.next();
+                return Some(paragraph);
+            }
+            Some((_, range)) if range.end <= marker_end => {
+                events.next();
             }
+            _ => return Some(paragraph),
         }
-        Tag::BlockQuote => blockquote([], []),
-        Tag::CodeBlock(kind) => {
-            let attributes = if let CodeBlockKind::Fenced(lang) = kind {
-                class(lang.to_string())
-            } else {
-                empty_attr()
-            };
-            code([attributes], [])
-        }
-        Tag::List(None) => ul([], []),
-        Tag::List(Some(1)) => ol([], []),
-        Tag::List(Some(start)) => ol([attr("start", start.to_string())], []),
-        Tag::Item => li([], []),
-        Tag::Table(_alignment) => table([], []),
-        Tag::TableHead => th([], []),
-        Tag::TableRow => tr([], []),
-        Tag::TableCell => td([], []),
-        Tag::Emphasis => em([], []),
-        Tag::Strong => strong([], []),
-        Tag::Link(_type, href_, title_) => a(
-            [
-                href(href_.to_string()),
-                attributes::title(title_.to_string()),
-            ],
-            [],
-        ),
-        Tag::Image(_type, src_, title_) => img(
-            [src(src_.to_string()), attributes::title(title_.to_string())],
-            [],
-        ),
-        Tag::Strikethrough => html::s([], []),
-        Tag::FootnoteDefinition(footnote_id) => footer(
-            [class("footnote-definition"), id(footnote_id.to_string())],
-            [],
+    }
+}
+
+/// the markdown tags which are rendered as block level elements
+fn is_block_tag(tag: &Tag) -> bool {
+    matches!(
+        tag,
+        Tag::Paragraph
+            | Tag::Heading(_)
+            | Tag::BlockQuote
+            | Tag::CodeBlock(_)
+            | Tag::List(_)
+            | Tag::Item
+            | Tag::FootnoteDefinition(_)
+            | Tag::Table(_)
+            | Tag::TableHead
+            | Tag::TableRow
+            | Tag::TableCell
+    )
+}
+
+/// create the heading element of this level
+fn heading<MSG>(level: u32, attributes: Vec<Attribute<MSG>>) -> Result<Node<MSG>, MarkdownError> {
+    let node = match level {
+        1 => h1(attributes, []),
+        2 => h2(attributes, []),
+        3 => h3(attributes, []),
+        4 => h4(attributes, []),
+        5 => h5(attributes, []),
+        6 => h6(attributes, []),
+        _ => return Err(MarkdownError::InvalidHeadingLevel(level)),
+    };
+    Ok(node)
+}
+
+/// create the attribute of the url if it is allowed by the url policy
+fn url_attribute<MSG>(
+    options: &MarkdownOptions,
+    url: &str,
+    kind: UrlKind,
+    attribute: fn(String) -> Attribute<MSG>,
+) -> Attribute<MSG> {
+    let url = match &options.url_policy {
+        Some(url_policy) => url_policy.filter(url, kind),
+        None => Some(url.to_string()),
+    };
+    match url {
+        Some(url) => attribute(url),
+        None => empty_attr(),
+    }
+}
+
+/// whether the url is allowed by the url policy, as opposed to being removed or replaced
+fn is_url_allowed(options: &MarkdownOptions, url: &str, kind: UrlKind) -> bool {
+    options
+        .url_policy
+        .as_ref()
+        .map_or(true, |url_policy| url_policy.is_allowed(url, kind))
+}
+
+/// the attribute for aligning the text of a table cell
+fn alignment_attribute<MSG>(
+    alignment: Alignment,
+    table_alignment: TableAlignment,
+) -> Attribute<MSG> {
+    let align = match alignment {
+        Alignment::None => return empty_attr(),
+        Alignment::Left => "left",
+        Alignment::Center => "center",
+        Alignment::Right => "right",
+    };
+    match table_alignment {
+        TableAlignment::Class => class(format!("text-{}", align)),
+        TableAlignment::Style => attr("style", format!("text-align:{}", align)),
+    }
+}
+
+/// create a `<pre><code>` block, the language is used as the class of the `code` element
+fn code_block<MSG>(info: &CodeFenceInfo, content: &str, options: &MarkdownOptions) -> Node<MSG> {
+    let classes: Vec<String> = info
+        .lang
+        .iter()
+        .map(|lang| format!("{}{}", options.code_class_prefix, lang))
+        .chain(info.classes.iter().cloned())
+        .collect();
+    let class_attr = if classes.is_empty() {
+        empty_attr()
+    } else {
+        class(classes.join(" "))
+    };
+    let id_attr = match &info.id {
+        Some(id_) => id(id_.to_string()),
+        None => empty_attr(),
+    };
+    let content = if info.line_numbers() || !info.highlighted_lines.is_empty() {
+        code_lines(info, content, options)
+    } else {
+        code_content(info, content, options)
+    };
+    let pre_node = pre([id_attr], [code([class_attr], content)]);
+    match info.title() {
+        Some(title) => figure(
+            [class("code-block")],
+            [figcaption([class("code-title")], [text(title)]), pre_node],
         ),
+        None => pre_node,
     }
 }
 
+/// wrap the code block in a `div.code-container` with a copy button
+fn copy_container<MSG>(code_block: Node<MSG>, on_copy: Attribute<MSG>) -> Node<MSG> {
+    div(
+        [class("code-container")],
+        [
+            code_block,
+            button(
+                [class("copy-button"), r#type("button"), on_copy],
+                [text("Copy")],
+            ),
+        ],
+    )
+}
+
+/// wrap each line of the code in a `span.line` with its line number in `data-line`,
+/// the highlighted lines also have the `highlighted` class
+fn code_lines<MSG>(
+    info: &CodeFenceInfo,
+    content: &str,
+    options: &MarkdownOptions,
+) -> Vec<Node<MSG>> {
+    line_contents(info, content, options)
+        .into_iter()
+        .enumerate()
+        .map(|(index, line)| {
+            let number = index + 1;
+            let line_class = if info.is_line_highlighted(number) {
+                "line highlighted"
+            } else {
+                "line"
+            };
+            let mut children = vec![];
+            if info.line_numbers() {
+                children.push(span([class("line-number")], [text(number)]));
+            }
+            children.extend(line);
+            span(
+                [class(line_class), attr("data-line", number.to_string())],
+                children,
+            )
+        })
+        .collect()
+}
+
+/// the content of each line of the code, highlighted if the language is supported
+#[cfg(feature = "highlight")]
+fn line_contents<MSG>(
+    info: &CodeFenceInfo,
+    content: &str,
+    options: &MarkdownOptions,
+) -> Vec<Vec<Node<MSG>>> {
+    info.lang
+        .as_ref()
+        .filter(|_| options.highlight)
+        .and_then(|lang| {
+            highlight::highlight_lines(content, lang, options.highlight_theme.as_ref())
+        })
+        .unwrap_or_else(|| plain_lines(content))
+}
+
+#[cfg(not(feature = "highlight"))]
+fn line_contents<MSG>(
+    _info: &CodeFenceInfo,
+    content: &str,
+    _options: &MarkdownOptions,
+) -> Vec<Vec<Node<MSG>>> {
+    plain_lines(content)
+}
+
+fn plain_lines<MSG>(content: &str) -> Vec<Vec<Node<MSG>>> {
+    content
+        .split_inclusive('\n')
+        .map(|line| vec![text(line)])
+        .collect()
+}
+
+/// the highlighted code if the language is supported, otherwise the code as is
+#[cfg(feature = "highlight")]
+fn code_content<MSG>(
+    info: &CodeFenceInfo,
+    content: &str,
+    options: &MarkdownOptions,
+) -> Vec<Node<MSG>> {
+    info.lang
+        .as_ref()
+        .filter(|_| options.highlight)
+        .and_then(|lang| highlight::highlight(content, lang, options.highlight_theme.as_ref()))
+        .unwrap_or_else(|| vec![text(content)])
+}
+
+#[cfg(not(feature = "highlight"))]
+fn code_content<MSG>(
+    _info: &CodeFenceInfo,
+    content: &str,
+    _options: &MarkdownOptions,
+) -> Vec<Node<MSG>> {
+    vec![text(content)]
+}
+
 /// parse the markdown and return the nodes
+///
+/// If the markdown can not be converted, the source is displayed as is
+/// in a `pre` element instead of panicking.
 pub fn parse<MSG>(src: &str) -> Vec<Node<MSG>> {
-    MdParser::new().parse(src)
+    parse_with_options(src, &MarkdownOptions::default())
 }
 
-fn maybe_title<MSG>(node: &Node<MSG>) -> Option<&str> {
-    if node.tag() == Some(&&"h1") {
-        let children = node.children();
-        if children.len() == 1 {
-            return children[0].as_text();
-        }
-    }
-    None
+/// parse the markdown and return the nodes, or the error encountered
+pub fn try_parse<MSG>(src: &str) -> Result<Vec<Node<MSG>>, MarkdownError> {
+    try_parse_with_options(src, &MarkdownOptions::default())
+}
+
+/// parse the markdown using the supplied options and return the nodes
+pub fn parse_with_options<MSG>(src: &str, options: &MarkdownOptions) -> Vec<Node<MSG>> {
+    MarkdownParser::with_options(options.clone()).parse(src)
+}
+
+/// parse the markdown using the supplied options and return the nodes,
+/// or the error encountered
+pub fn try_parse_with_options<MSG>(
+    src: &str,
+    options: &MarkdownOptions,
+) -> Result<Vec<Node<MSG>>, MarkdownError> {
+    MarkdownParser::with_options(options.clone()).try_parse(src)
+}
+
+/// the nodes used when the markdown could not be converted
+fn fallback<MSG>(src: &str, e: MarkdownError) -> Vec<Node<MSG>> {
+    log::error!("unable to parse markdown: {}", e);
+    vec![pre([], [text(src)])]
+}
+
+fn is_tag<MSG>(node: &Node<MSG>, tag: &str) -> bool {
+    node.tag().map_or(false, |t| *t == tag)
 }
 
-fn find_title<MSG>(nodes: &[Node<MSG>]) -> Option<&str> {
-    for node in nodes.iter() {
-        if let Some(title) = maybe_title(node) {
+/// return the flattened text of the first heading with this tag,
+/// removing the heading from the nodes if `strip` is true
+fn take_heading<MSG>(nodes: &mut Vec<Node<MSG>>, tag: &str, strip: bool) -> Option<String> {
+    for index in 0..nodes.len() {
+        if is_tag(&nodes[index], tag) {
+            let title = heading::node_text(&nodes[index]);
+            if strip {
+                nodes.remove(index);
+            }
             return Some(title);
-        } else {
-            if let Some(title) = find_title(node.children()) {
+        }
+        if let Node::Element(element) = &mut nodes[index] {
+            if let Some(title) = take_heading(&mut element.children, tag, strip) {
                 return Some(title);
             }
         }
@@ -161,9 +1431,138 @@ fn find_title<MSG>(nodes: &[Node<MSG>]) -> Option<&str> {
     None
 }
 
+/// parse the markdown and return the table of contents and the nodes
+pub fn parse_with_toc<MSG>(src: &str) -> (Vec<Toc>, Vec<Node<MSG>>) {
+    MarkdownParser::new().parse_with_toc(src)
+}
+
+/// parse the markdown and return the table of contents and the nodes,
+/// or the error encountered
+pub fn try_parse_with_toc<MSG>(src: &str) -> Result<(Vec<Toc>, Vec<Node<MSG>>), MarkdownError> {
+    MarkdownParser::new().try_parse_with_toc(src)
+}
+
+/// parse the markdown and return the nodes together with the byte range in the source
+/// of each of the top level nodes
+pub fn parse_with_spans<MSG>(src: &str) -> (Vec<Node<MSG>>, Vec<Option<Range<usize>>>) {
+    MarkdownParser::new().parse_with_spans(src)
+}
+
+/// parse the markdown and return the nodes together with the byte range in the source
+/// of each of the top level nodes, or the error encountered
+pub fn try_parse_with_spans<MSG>(
+    src: &str,
+) -> Result<(Vec<Node<MSG>>, Vec<Option<Range<usize>>>), MarkdownError> {
+    MarkdownParser::new().try_parse_with_spans(src)
+}
+
+/// parse the markdown and return the nodes together with the footnotes
+/// which are referenced but not defined, or defined but never referenced
+pub fn parse_with_footnotes<MSG>(src: &str) -> (Vec<Node<MSG>>, FootnoteReport) {
+    MarkdownParser::new().parse_with_footnotes(src)
+}
+
+/// parse the markdown and return the nodes together with the footnotes
+/// which are not linked, or the error encountered
+pub fn try_parse_with_footnotes<MSG>(
+    src: &str,
+) -> Result<(Vec<Node<MSG>>, FootnoteReport), MarkdownError> {
+    MarkdownParser::new().try_parse_with_footnotes(src)
+}
+
+/// parse the markdown and return the front matter and the nodes
+pub fn parse_with_front_matter_map<MSG>(src: &str) -> (Option<FrontMatter>, Vec<Node<MSG>>) {
+    MarkdownParser::new().parse_with_front_matter_map(src)
+}
+
+/// parse the markdown and return the front matter and the nodes,
+/// or the error encountered
+pub fn try_parse_with_front_matter_map<MSG>(
+    src: &str,
+) -> Result<(Option<FrontMatter>, Vec<Node<MSG>>), MarkdownError> {
+    MarkdownParser::new().try_parse_with_front_matter_map(src)
+}
+
+/// parse the markdown and return the front matter deserialized into `T` and the nodes
+#[cfg(any(feature = "front-matter-yaml", feature = "front-matter-toml"))]
+pub fn parse_with_front_matter<MSG, T>(src: &str) -> (Option<T>, Vec<Node<MSG>>)
+where
+    T: serde::de::DeserializeOwned,
+{
+    MarkdownParser::new().parse_with_front_matter(src)
+}
+
+/// parse the markdown and return the front matter deserialized into `T` and the nodes,
+/// or the error encountered
+#[cfg(any(feature = "front-matter-yaml", feature = "front-matter-toml"))]
+pub fn try_parse_with_front_matter<MSG, T>(
+    src: &str,
+) -> Result<(Option<T>, Vec<Node<MSG>>), MarkdownError>
+where
+    T: serde::de::DeserializeOwned,
+{
+    MarkdownParser::new().try_parse_with_front_matter(src)
+}
+
 /// parse the markdown and return the first encountered h1 text and the nodes
 pub fn parse_with_title<MSG>(src: &str) -> (Option<String>, Vec<Node<MSG>>) {
-    let nodes = parse(src);
-    let title = find_title(&nodes).map(|t| t.to_string());
-    (title, nodes)
+    MarkdownParser::new().parse_with_title(src)
+}
+
+/// parse the markdown and return the first encountered h1 text and the nodes,
+/// or the error encountered
+pub fn try_parse_with_title<MSG>(
+    src: &str,
+) -> Result<(Option<String>, Vec<Node<MSG>>), MarkdownError> {
+    MarkdownParser::new().try_parse_with_title(src)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// a callback for the parser which records the values it is called with
+    pub(super) fn recorder<T: 'static>() -> (Rc<RefCell<Vec<T>>>, impl Fn(T) + 'static) {
+        let values = Rc::new(RefCell::new(vec![]));
+        let record = {
+            let values = Rc::clone(&values);
+            move |value: T| values.borrow_mut().push(value)
+        };
+        (values, record)
+    }
+
+    /// the number of `tag` elements which have a listener of the `event`
+    pub(super) fn count_listeners<MSG>(nodes: &[Node<MSG>], tag: &str, event: &str) -> usize {
+        nodes
+            .iter()
+            .map(|node| match node {
+                Node::Element(element) => {
+                    let has_listener = node.tag() == Some(&tag)
+                        && element.attrs.iter().any(|attr| attr.name == event);
+                    usize::from(has_listener) + count_listeners(&element.children, tag, event)
+                }
+                _ => 0,
+            })
+            .sum()
+    }
+
+    #[test]
+    fn copy_the_source_of_the_fenced_code_blocks() {
+        let md = "```rust\nlet x = '\\n';\n```\n\n```quote\n> hello\n```\n\n    indented\n\n$x$";
+        let options = MarkdownOptions::default().math(true);
+        let (copied, record) = recorder();
+        let parser = MarkdownParser::with_options(options)
+            .add_code_fence_processor("quote", |_info: &CodeFenceInfo, code: &str| {
+                Some(blockquote([], [text(code)]))
+            })
+            .on_code_copy(record);
+        let nodes = parser.parse(md);
+        assert_eq!(count_listeners(&nodes, "button", "click"), 2);
+        assert_eq!(
+            copied.take(),
+            vec!["let x = '\\n';\n".to_string(), "> hello\n".to_string()]
+        );
+    }
 }
@@ -0,0 +1,313 @@
+//! Admonitions, the GitHub style `> [!NOTE]` blockquotes and the `!!! note` blocks
+use crate::markdown::incremental;
+use crate::markdown::source_map::LineOffsets;
+use pulldown_cmark::{Event, Options, Parser};
+use sauron::*;
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+/// the default kinds, the GitHub alerts followed by the common kinds of the `!!!` admonitions
+const DEFAULT_KINDS: [(&str, &str); 7] = [
+    ("note", "Note"),
+    ("tip", "Tip"),
+    ("important", "Important"),
+    ("warning", "Warning"),
+    ("caution", "Caution"),
+    ("info", "Info"),
+    ("danger", "Danger"),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+struct AdmonitionKind {
+    title: String,
+    icon: Option<String>,
+}
+
+/// The kinds of admonitions which are recognized, with their default title and icon.
+///
+/// The admonitions are rendered as `<div class="admonition note">` with a
+/// `<p class="admonition-title">`, or as `<details>` with a `<summary>` when collapsible.
+/// ```rust
+/// use sauron_markdown::{Admonitions, MarkdownOptions};
+///
+/// let admonitions = Admonitions::new()
+///     .kind("success", "Success", Some("✔"))
+///     .kind("warning", "Careful", None::<String>);
+/// let options = MarkdownOptions::default().admonitions(Some(admonitions));
+/// ```
+///
+/// The supported syntax:
+/// - `> [!NOTE]` blockquotes, followed by an optional custom title.
+///   `> [!NOTE]-` is collapsed and `> [!NOTE]+` is collapsible but initially open.
+/// - `!!! note "Custom title"` followed by the content indented with 4 spaces,
+///   `??? note` is collapsed and `???+ note` is collapsible but initially open.
+///   An empty title `!!! note ""` hides the title.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Admonitions {
+    kinds: BTreeMap<String, AdmonitionKind>,
+}
+
+impl Default for Admonitions {
+    fn default() -> Self {
+        DEFAULT_KINDS
+            .iter()
+            .fold(Self::empty(), |admonitions, (name, title)| {
+                admonitions.kind(name, title, None::<String>)
+            })
+    }
+}
+
+impl Admonitions {
+    /// create the default kinds: `note`, `tip`, `important`, `warning`, `caution`,
+    /// `info` and `danger`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// create a registry without any kinds
+    pub fn empty() -> Self {
+        Self {
+            kinds: BTreeMap::new(),
+        }
+    }
+
+    /// add a kind or replace the existing kind with this name,
+    /// the name is matched case-insensitively
+    pub fn kind(
+        mut self,
+        name: impl ToString,
+        title: impl ToString,
+        icon: Option<impl ToString>,
+    ) -> Self {
+        self.kinds.insert(
+            name.to_string().to_lowercase(),
+            AdmonitionKind {
+                title: title.to_string(),
+                icon: icon.map(|icon| icon.to_string()),
+            },
+        );
+        self
+    }
+
+    /// the element of the GitHub style admonition, if the blockquote starts with `[!KIND]`
+    pub(crate) fn github<MSG>(&self, blockquote: &str) -> Option<Node<MSG>> {
+        let line = blockquote.lines().next()?;
+        self.container(&github_header(line)?)
+    }
+
+    /// the header and the range of the content of the `!!!` admonition
+    /// which starts at this position of the markdown
+    pub(crate) fn fence_block(
+        &self,
+        markdown: &str,
+        start: usize,
+    ) -> Option<(AdmonitionHeader, Range<usize>)> {
+        let header_end = markdown[start..]
+            .find('\n')
+            .map_or(markdown.len(), |end| start + end + 1);
+        let header = fence_header(markdown[start..header_end].trim_end())?;
+        if !self.kinds.contains_key(&header.kind) {
+            return None;
+        }
+        // the content are the indented lines, blank lines in between are included
+        let mut end = header_end;
+        let mut position = header_end;
+        for line in markdown[header_end..].split_inclusive('\n') {
+            position += line.len();
+            if line.starts_with("    ") || line.starts_with('\t') {
+                end = position;
+            } else if !line.trim().is_empty() {
+                break;
+            }
+        }
+        Some((header, header_end..end))
+    }
+
+    /// the element of the admonition without its content,
+    /// `None` if the kind is not registered
+    pub(crate) fn container<MSG>(&self, header: &AdmonitionHeader) -> Option<Node<MSG>> {
+        let kind = self.kinds.get(&header.kind)?;
+        let class_attr = class(format!("admonition {}", header.kind));
+        let title = match &header.title {
+            Some(title) if !title.is_empty() || header.open.is_none() => title,
+            _ => &kind.title,
+        };
+        let mut title_nodes = vec![];
+        if let Some(icon) = &kind.icon {
+            title_nodes.push(span([class("admonition-icon")], [text(icon)]));
+        }
+        title_nodes.push(text(title));
+        let container = match header.open {
+            Some(open) => {
+                let open_attr = if open { attr("open", "") } else { empty_attr() };
+                details(
+                    [class_attr, open_attr],
+                    [summary([class("admonition-title")], title_nodes)],
+                )
+            }
+            None if title.is_empty() => div([class_attr], []),
+            None => div([class_attr], [p([class("admonition-title")], title_nodes)]),
+        };
+        Some(container)
+    }
+}
+
+/// The first line of an admonition
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AdmonitionHeader {
+    /// the kind in lowercase
+    kind: String,
+    /// the custom title, an empty title hides the title
+    title: Option<String>,
+    /// whether the collapsible admonition is initially open, `None` if it is not collapsible
+    open: Option<bool>,
+}
+
+/// parse the first line of the blockquote, ie: `> [!NOTE] Custom title`
+fn github_header(line: &str) -> Option<AdmonitionHeader> {
+    let rest = line.trim_start().strip_prefix('>')?.trim_start();
+    let rest = rest.strip_prefix("[!")?;
+    let end = rest.find(']')?;
+    let kind = &rest[..end];
+    if kind.is_empty()
+        || !kind
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    {
+        return None;
+    }
+    let rest = &rest[end + 1..];
+    let (open, rest) = if let Some(rest) = rest.strip_prefix('+') {
+        (Some(true), rest)
+    } else if let Some(rest) = rest.strip_prefix('-') {
+        (Some(false), rest)
+    } else {
+        (None, rest)
+    };
+    let title = rest.trim();
+    Some(AdmonitionHeader {
+        kind: kind.to_lowercase(),
+        title: if title.is_empty() {
+            None
+        } else {
+            Some(title.to_string())
+        },
+        open,
+    })
+}
+
+/// parse the first line of the `!!!` admonition, ie: `!!! note "Custom title"`
+fn fence_header(line: &str) -> Option<AdmonitionHeader> {
+    let (open, rest) = if let Some(rest) = line.strip_prefix("???+") {
+        (Some(true), rest)
+    } else if let Some(rest) = line.strip_prefix("???") {
+        (Some(false), rest)
+    } else if let Some(rest) = line.strip_prefix("!!!") {
+        (None, rest)
+    } else {
+        return None;
+    };
+    if !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let rest = rest.trim();
+    let (kind, title) = match rest.find(char::is_whitespace) {
+        Some(end) => (&rest[..end], rest[end..].trim()),
+        None => (rest, ""),
+    };
+    if kind.is_empty() {
+        return None;
+    }
+    // the words other than the quoted title are ignored
+    let title = title
+        .find('"')
+        .and_then(|start| {
+            let title = &title[start + 1..];
+            title.find('"').map(|end| &title[..end])
+        })
+        .map(ToString::to_string);
+    Some(AdmonitionHeader {
+        kind: kind.to_lowercase(),
+        title,
+        open,
+    })
+}
+
+/// remove the indentation of the content of the `!!!` admonition at this range of the markdown,
+/// returning the offsets of its lines in the source
+pub(crate) fn dedent(
+    markdown: &str,
+    content: Range<usize>,
+    offsets: &LineOffsets,
+) -> (String, LineOffsets) {
+    let mut dedented = String::new();
+    let mut dedented_offsets = LineOffsets::empty();
+    let mut line_start = content.start;
+    for line in markdown[content].split_inclusive('\n') {
+        let stripped = line
+            .strip_prefix("    ")
+            .or_else(|| line.strip_prefix('\t'))
+            .unwrap_or(line);
+        let indent = line.len() - stripped.len();
+        dedented_offsets.push_line(dedented.len(), offsets.source_offset(line_start + indent));
+        dedented.push_str(stripped);
+        line_start += line.len();
+    }
+    (dedented, dedented_offsets)
+}
+
+/// the link reference definitions of the markdown, which is the text in between its top
+/// level blocks. They are added to the content of the `!!!` admonitions, so the links
+/// in the content can use the definitions of the whole document
+pub(crate) fn definitions(markdown: &str, options: Options) -> String {
+    let mut blocks = vec![];
+    let mut depth = 0;
+    for (ev, range) in Parser::new_ext(markdown, options).into_offset_iter() {
+        if depth == 0 {
+            blocks.push(range);
+        }
+        match ev {
+            Event::Start(_) => depth += 1,
+            Event::End(_) => depth -= 1,
+            _ => (),
+        }
+    }
+    incremental::definitions(markdown, &blocks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn github_headers() {
+        let header = github_header("> [!WARNING]- Read this").expect("must be a header");
+        assert_eq!(header.kind, "warning");
+        assert_eq!(header.title.as_deref(), Some("Read this"));
+        assert_eq!(header.open, Some(false));
+        assert_eq!(github_header("> [link](url)"), None);
+        assert_eq!(github_header("> [!not a kind]"), None);
+    }
+
+    #[test]
+    fn fence_block() {
+        let md = "intro\n!!! tip \"Pro tip\"\n    first\n\n    second\n\nafter\n";
+        let start = md.find('!').expect("must have the header");
+        let (header, content) = Admonitions::new()
+            .fence_block(md, start)
+            .expect("must be an admonition");
+        assert_eq!(header.kind, "tip");
+        assert_eq!(header.title.as_deref(), Some("Pro tip"));
+        assert_eq!(header.open, None);
+        let (dedented, offsets) = dedent(md, content, &LineOffsets::new(0));
+        assert_eq!(dedented, "first\n\nsecond\n");
+        assert_eq!(&md[offsets.source_range(7..13)], "second");
+        assert_eq!(Admonitions::empty().fence_block(md, start), None);
+    }
+
+    #[test]
+    fn reference_definitions() {
+        let md = "text\n\n[docs]: /docs\n\n> [other]: /other\n";
+        assert_eq!(definitions(md, Options::empty()), "[docs]: /docs\n");
+    }
+}
@@ -0,0 +1,98 @@
+//! Deterministic keys of the top level blocks, used by sauron for matching the nodes when diffing
+use sauron::*;
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// the 64-bit FNV-1a hash of the source text of the block.
+/// The std hashers are not guaranteed to be the same across rust releases,
+/// while the keys of the server rendered and the hydrated nodes have to match
+pub(crate) fn content_hash(text: &str) -> u64 {
+    text.bytes().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// Creates the keys of the blocks from their content hash,
+/// blocks with the same content are distinguished by their occurrence index
+pub(crate) struct BlockKeys {
+    occurrences: BTreeMap<u64, usize>,
+}
+
+impl BlockKeys {
+    pub(crate) fn new() -> Self {
+        Self {
+            occurrences: BTreeMap::new(),
+        }
+    }
+
+    /// the key of the next block with this content hash
+    pub(crate) fn next(&mut self, hash: u64) -> String {
+        let occurrence = self.occurrences.entry(hash).or_insert(0);
+        let key = format!("{:016x}-{}", hash, occurrence);
+        *occurrence += 1;
+        key
+    }
+}
+
+/// add the `key` attribute to the node, text nodes are left as is
+pub(crate) fn set_key<MSG>(node: &mut Node<MSG>, key: String) {
+    if let Node::Element(element) = node {
+        element.attrs.push(attr("key", key));
+    }
+}
+
+/// add the keys to the nodes which came from the same block,
+/// the index of the node is appended when the block has more than one node
+pub(crate) fn set_block_keys<MSG>(nodes: &mut [Node<MSG>], key: &str) {
+    let single = nodes.len() == 1;
+    for (index, node) in nodes.iter_mut().enumerate() {
+        if single {
+            set_key(node, key.to_string());
+        } else {
+            set_key(node, format!("{}-{}", key, index));
+        }
+    }
+}
+
+/// add the keys derived from the source of each of the top level nodes,
+/// nodes which are not from the source such as the footnotes section are keyed by their order
+pub(crate) fn set_span_keys<MSG>(
+    src: &str,
+    nodes: &mut [Node<MSG>],
+    spans: &[Option<Range<usize>>],
+) {
+    let mut keys = BlockKeys::new();
+    for (node, span) in nodes.iter_mut().zip(spans) {
+        let hash = match span {
+            Some(span) => content_hash(&src[span.clone()]),
+            None => 0,
+        };
+        set_key(node, keys.next(hash));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn occurrence_index() {
+        let mut keys = BlockKeys::new();
+        let hash = content_hash("same");
+        let first = keys.next(hash);
+        let second = keys.next(hash);
+        assert!(first.ends_with("-0"));
+        assert!(second.ends_with("-1"));
+        assert_eq!(first[..16], second[..16]);
+    }
+
+    #[test]
+    fn stable_hash() {
+        assert_eq!(content_hash(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(content_hash("a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(content_hash("foobar"), 0x8594_4171_f739_67e8);
+    }
+}
@@ -0,0 +1,200 @@
+//! Parsing of the info string of fenced code blocks
+use pulldown_cmark::CodeBlockKind;
+use std::ops::RangeInclusive;
+
+/// The parsed info string of a fenced code block.
+///
+/// ```rust
+/// use sauron_markdown::CodeFenceInfo;
+///
+/// let info = CodeFenceInfo::parse("rust,ignore {.numberLines #main}");
+/// assert_eq!(info.lang.as_deref(), Some("rust"));
+/// assert_eq!(info.flags, vec!["ignore"]);
+/// assert_eq!(info.classes, vec!["numberLines"]);
+/// assert_eq!(info.id.as_deref(), Some("main"));
+///
+/// let info = CodeFenceInfo::parse(r#"rust {1,3-5} linenos title="main.rs""#);
+/// assert!(info.is_line_highlighted(4));
+/// assert!(info.line_numbers());
+/// assert_eq!(info.title(), Some("main.rs"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CodeFenceInfo {
+    /// the info string as written after the opening fence
+    pub raw: String,
+    /// the language of the code, ie: `rust` in `rust,ignore`
+    pub lang: Option<String>,
+    /// the flags following the language, ie: `ignore` in `rust,ignore`
+    pub flags: Vec<String>,
+    /// the classes in the attribute block, ie: `numberLines` in `{.numberLines}`
+    pub classes: Vec<String>,
+    /// the id in the attribute block, ie: `main` in `{#main}`
+    pub id: Option<String>,
+    /// the key value pairs in the attribute block, ie: `title="main.rs"`
+    pub attributes: Vec<(String, String)>,
+    /// the lines to be highlighted, ie: `1` and `3` to `5` in `{1,3-5}`
+    pub highlighted_lines: Vec<RangeInclusive<usize>>,
+}
+
+impl CodeFenceInfo {
+    /// parse the info string of a fenced code block
+    pub fn parse(info: &str) -> Self {
+        let info = info.trim();
+        let (head, attr_block) = match info.find('{') {
+            Some(start) => (&info[..start], &info[start + 1..]),
+            None => (info, ""),
+        };
+
+        let mut fence_info = CodeFenceInfo {
+            raw: info.to_string(),
+            ..Default::default()
+        };
+        let mut head_tokens = tokenize_attributes(head).into_iter();
+        fence_info.lang = head_tokens.next();
+        // the line ranges are only in the attribute block,
+        // so the numbers after the language such as `text 2024` are flags
+        let tokens = head_tokens.map(|token| (token, false)).chain(
+            tokenize_attributes(attr_block)
+                .into_iter()
+                .map(|token| (token, true)),
+        );
+        for (token, in_attr_block) in tokens {
+            if let Some(class) = token.strip_prefix('.') {
+                fence_info.classes.push(class.to_string());
+            } else if let Some(id) = token.strip_prefix('#') {
+                fence_info.id = Some(id.to_string());
+            } else if let Some(range) = parse_line_range(&token).filter(|_| in_attr_block) {
+                fence_info.highlighted_lines.push(range);
+            } else if let Some((key, value)) = split_key_value(&token) {
+                fence_info.attributes.push((key, value));
+            } else {
+                fence_info.flags.push(token);
+            }
+        }
+
+        // pandoc style `{.rust .numberLines}`, the first class is the language
+        if fence_info.lang.is_none() && !fence_info.classes.is_empty() {
+            fence_info.lang = Some(fence_info.classes.remove(0));
+        }
+        fence_info
+    }
+
+    /// the info of the code block, indented code blocks has no info
+    pub(crate) fn from_kind(kind: &CodeBlockKind) -> Self {
+        match kind {
+            CodeBlockKind::Fenced(info) => Self::parse(info),
+            CodeBlockKind::Indented => Self::default(),
+        }
+    }
+
+    /// return the value of the attribute with this key
+    pub fn attribute(&self, key: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(k, _v)| k == key)
+            .map(|(_k, v)| v.as_str())
+    }
+
+    /// the file name or caption shown above the code, ie: `title="main.rs"`
+    pub fn title(&self) -> Option<&str> {
+        self.attribute("title")
+    }
+
+    /// whether the line numbers are shown, with the `linenos` flag or pandoc's `.numberLines`
+    pub fn line_numbers(&self) -> bool {
+        self.flags.iter().any(|flag| flag == "linenos")
+            || self.classes.iter().any(|class| class == "numberLines")
+    }
+
+    /// whether the line is highlighted, the first line is `1`
+    pub fn is_line_highlighted(&self, line: usize) -> bool {
+        self.highlighted_lines
+            .iter()
+            .any(|range| range.contains(&line))
+    }
+}
+
+/// split the info string by whitespace, commas and the closing brace,
+/// keeping quoted values intact
+fn tokenize_attributes(block: &str) -> Vec<String> {
+    let mut tokens = vec![];
+    let mut current = String::new();
+    let mut in_quote = false;
+    for ch in block.chars() {
+        match ch {
+            '"' => {
+                in_quote = !in_quote;
+                current.push(ch);
+            }
+            c if (c.is_whitespace() || c == ',' || c == '}') && !in_quote => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            _ => current.push(ch),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+/// parse a line range such as `3` or `3-5`
+fn parse_line_range(token: &str) -> Option<RangeInclusive<usize>> {
+    match token.split_once('-') {
+        Some((start, end)) => Some(start.parse().ok()?..=end.parse().ok()?),
+        None => {
+            let line = token.parse().ok()?;
+            Some(line..=line)
+        }
+    }
+}
+
+/// split `key="value"` into its key and unquoted value
+fn split_key_value(token: &str) -> Option<(String, String)> {
+    let (key, value) = token.split_once('=')?;
+    let value = value.trim_matches('"');
+    Some((key.to_string(), value.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_language() {
+        let info = CodeFenceInfo::parse("rust");
+        assert_eq!(info.lang.as_deref(), Some("rust"));
+        assert!(info.flags.is_empty());
+    }
+
+    #[test]
+    fn pandoc_attributes() {
+        let info = CodeFenceInfo::parse(r#"{.haskell .numberLines title="main hs"}"#);
+        assert_eq!(info.lang.as_deref(), Some("haskell"));
+        assert_eq!(info.classes, vec!["numberLines"]);
+        assert_eq!(info.attribute("title"), Some("main hs"));
+    }
+
+    #[test]
+    fn line_ranges() {
+        let info = CodeFenceInfo::parse(r#"rust {1, 3-5} title="main.rs" linenos"#);
+        assert_eq!(info.lang.as_deref(), Some("rust"));
+        assert_eq!(info.highlighted_lines, vec![1..=1, 3..=5]);
+        assert_eq!(info.title(), Some("main.rs"));
+        assert!(info.line_numbers());
+        assert!(!info.is_line_highlighted(2));
+        assert!(info.is_line_highlighted(5));
+    }
+
+    #[test]
+    fn numbers_outside_of_the_attribute_block() {
+        let info = CodeFenceInfo::parse("text 2024");
+        assert!(info.highlighted_lines.is_empty());
+        assert_eq!(info.flags, vec!["2024"]);
+        let info = CodeFenceInfo::parse("rust 1-2");
+        assert!(info.highlighted_lines.is_empty());
+        assert_eq!(info.flags, vec!["1-2"]);
+    }
+}
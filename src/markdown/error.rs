@@ -0,0 +1,19 @@
+//! Errors that can happen while converting markdown events into sauron nodes
+use thiserror::Error;
+
+/// all the possible error when converting markdown into nodes
+#[derive(Debug, Error)]
+pub enum MarkdownError {
+    /// an element was closed or a child was added while there is no open element
+    #[error("spine underflow: there is no open element")]
+    SpineUnderflow,
+    /// heading level is not within h1..h6
+    #[error("invalid heading level: {0}")]
+    InvalidHeadingLevel(u32),
+    /// the child node can not be attached to its parent node
+    #[error("unable to attach node: {0}")]
+    AttachNode(String),
+    /// the front matter can not be deserialized
+    #[error("invalid front matter: {0}")]
+    FrontMatter(String),
+}
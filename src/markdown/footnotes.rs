@@ -0,0 +1,133 @@
+//! Collects the footnote references and definitions into a numbered footnote section
+use crate::markdown::MarkdownError;
+use sauron::*;
+use std::collections::BTreeMap;
+
+/// The footnotes which are not linked to each other,
+/// returned by [`MarkdownParser::parse_with_footnotes`](crate::MarkdownParser::parse_with_footnotes)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FootnoteReport {
+    /// the names of the footnotes which are referenced but not defined,
+    /// in the order they are referenced
+    pub undefined: Vec<String>,
+    /// the names of the footnotes which are defined but never referenced
+    pub unreferenced: Vec<String>,
+}
+
+impl FootnoteReport {
+    /// whether all of the footnote references and definitions are linked to each other
+    pub fn is_empty(&self) -> bool {
+        self.undefined.is_empty() && self.unreferenced.is_empty()
+    }
+}
+
+/// The footnotes encountered in the markdown
+pub(crate) struct Footnotes<MSG> {
+    /// the footnote names in the order they are first referenced,
+    /// and the number of times they are referenced
+    references: Vec<(String, usize)>,
+    /// the `li` element of the footnote definitions
+    definitions: BTreeMap<String, Node<MSG>>,
+}
+
+impl<MSG> Footnotes<MSG> {
+    pub(crate) fn new() -> Self {
+        Self {
+            references: vec![],
+            definitions: BTreeMap::new(),
+        }
+    }
+
+    /// create the numbered link to the footnote definition
+    pub(crate) fn reference(&mut self, name: &str) -> Node<MSG> {
+        let index = match self.references.iter().position(|(n, _count)| n == name) {
+            Some(index) => index,
+            None => {
+                self.references.push((name.to_string(), 0));
+                self.references.len() - 1
+            }
+        };
+        let count = &mut self.references[index].1;
+        *count += 1;
+        let number = index + 1;
+        sup(
+            [],
+            [a(
+                [
+                    href(format!("#{}", definition_id(name))),
+                    id(reference_id(name, *count)),
+                ],
+                [text(number)],
+            )],
+        )
+    }
+
+    /// the element which will contain the content of the footnote definition
+    pub(crate) fn definition_container(name: &str) -> Node<MSG> {
+        li([id(definition_id(name))], [])
+    }
+
+    /// store the assembled footnote definition
+    pub(crate) fn define(&mut self, name: &str, definition: Node<MSG>) {
+        self.definitions.insert(name.to_string(), definition);
+    }
+
+    /// the section containing the referenced footnote definitions ordered by their number,
+    /// each with the backlinks to its references, and the footnotes which are not linked
+    pub(crate) fn into_section(
+        mut self,
+    ) -> Result<(Option<Node<MSG>>, FootnoteReport), MarkdownError> {
+        let mut items = vec![];
+        let mut report = FootnoteReport::default();
+        for (name, count) in self.references.iter() {
+            match self.definitions.remove(name) {
+                Some(mut definition) => {
+                    let backlinks = (1..=*count).map(|nth| {
+                        let mut label = vec![text("↩")];
+                        if nth > 1 {
+                            label.push(sup([], [text(nth)]));
+                        }
+                        a(
+                            [
+                                href(format!("#{}", reference_id(name, nth))),
+                                class("footnote-backref"),
+                            ],
+                            label,
+                        )
+                    });
+                    definition
+                        .add_children(backlinks)
+                        .map_err(|e| MarkdownError::AttachNode(e.to_string()))?;
+                    items.push(definition);
+                }
+                None => {
+                    log::warn!("footnote `{}` is referenced but not defined", name);
+                    report.undefined.push(name.clone());
+                }
+            }
+        }
+        for name in self.definitions.keys() {
+            log::warn!("footnote `{}` is defined but never referenced", name);
+            report.unreferenced.push(name.clone());
+        }
+        let section = if items.is_empty() {
+            None
+        } else {
+            Some(section([class("footnotes")], [ol([], items)]))
+        };
+        Ok((section, report))
+    }
+}
+
+fn definition_id(name: &str) -> String {
+    format!("fn-{}", name)
+}
+
+/// the id of the nth reference to the footnote, starting from 1
+fn reference_id(name: &str, nth: usize) -> String {
+    if nth == 1 {
+        format!("fnref-{}", name)
+    } else {
+        format!("fnref-{}-{}", name, nth)
+    }
+}
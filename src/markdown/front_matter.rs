@@ -0,0 +1,270 @@
+//! Detecting and parsing the YAML or TOML front matter at the start of the markdown
+#[cfg(any(feature = "front-matter-yaml", feature = "front-matter-toml"))]
+use crate::markdown::MarkdownError;
+use std::collections::BTreeMap;
+
+/// The format of the front matter, determined by its fence
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FrontMatterFormat {
+    /// front matter enclosed in `---`
+    Yaml,
+    /// front matter enclosed in `+++`
+    Toml,
+}
+
+/// A value in the front matter
+#[derive(Debug, Clone, PartialEq)]
+pub enum FrontMatterValue {
+    /// an empty value
+    Null,
+    /// true or false
+    Bool(bool),
+    /// a whole number
+    Integer(i64),
+    /// a floating point number
+    Float(f64),
+    /// a string, dates are also converted into string
+    String(String),
+    /// a list of values
+    List(Vec<FrontMatterValue>),
+    /// a nested map of values
+    Map(BTreeMap<String, FrontMatterValue>),
+}
+
+impl FrontMatterValue {
+    /// return the string if this value is a string
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            FrontMatterValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+/// The front matter of the markdown.
+///
+/// The front matter is always stripped from the markdown, but the values are only available
+/// when the `front-matter-yaml` or `front-matter-toml` feature of the format is enabled,
+/// otherwise only the raw text is available.
+/// ```rust
+/// use sauron_markdown::{FrontMatter, FrontMatterFormat};
+///
+/// let (front_matter, body) = FrontMatter::extract("---\ntitle: Hello\n---\n# Content");
+/// let front_matter = front_matter.expect("must have front matter");
+/// assert_eq!(front_matter.format, FrontMatterFormat::Yaml);
+/// assert_eq!(front_matter.raw, "title: Hello\n");
+/// assert_eq!(body, "# Content");
+/// #[cfg(not(feature = "front-matter-yaml"))]
+/// assert_eq!(front_matter.values(), None);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrontMatter {
+    /// the format of the front matter
+    pub format: FrontMatterFormat,
+    /// the text in between the fences
+    pub raw: String,
+    /// `None` if the feature of the format is not enabled or the front matter is invalid
+    values: Option<BTreeMap<String, FrontMatterValue>>,
+}
+
+impl FrontMatter {
+    /// split the front matter from the rest of the markdown
+    pub fn extract(src: &str) -> (Option<Self>, &str) {
+        match split(src) {
+            Some((format, raw, body_start)) => {
+                let front_matter = Self {
+                    format,
+                    raw: raw.to_string(),
+                    values: parse_values(format, raw),
+                };
+                (Some(front_matter), &src[body_start..])
+            }
+            None => (None, src),
+        }
+    }
+
+    /// return the top level value with this key
+    pub fn get(&self, key: &str) -> Option<&FrontMatterValue> {
+        self.values.as_ref()?.get(key)
+    }
+
+    /// all of the top level values,
+    /// `None` if the feature of the format is not enabled or the front matter is invalid
+    pub fn values(&self) -> Option<&BTreeMap<String, FrontMatterValue>> {
+        self.values.as_ref()
+    }
+
+    /// the `title` value of the front matter
+    pub fn title(&self) -> Option<&str> {
+        self.get("title").and_then(FrontMatterValue::as_str)
+    }
+
+    /// deserialize the front matter into `T`
+    #[cfg(any(feature = "front-matter-yaml", feature = "front-matter-toml"))]
+    pub fn deserialize<T>(&self) -> Result<T, MarkdownError>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        match self.format {
+            #[cfg(feature = "front-matter-yaml")]
+            FrontMatterFormat::Yaml => serde_yaml::from_str(&self.raw)
+                .map_err(|e| MarkdownError::FrontMatter(e.to_string())),
+            #[cfg(feature = "front-matter-toml")]
+            FrontMatterFormat::Toml => {
+                toml::from_str(&self.raw).map_err(|e| MarkdownError::FrontMatter(e.to_string()))
+            }
+            #[cfg(not(feature = "front-matter-yaml"))]
+            FrontMatterFormat::Yaml => Err(MarkdownError::FrontMatter(
+                "the `front-matter-yaml` feature is not enabled".to_string(),
+            )),
+            #[cfg(not(feature = "front-matter-toml"))]
+            FrontMatterFormat::Toml => Err(MarkdownError::FrontMatter(
+                "the `front-matter-toml` feature is not enabled".to_string(),
+            )),
+        }
+    }
+}
+
+/// return the format, the text inside the fences and the byte offset where the markdown starts,
+/// the front matter must start at the first line and have a closing fence
+fn split(src: &str) -> Option<(FrontMatterFormat, &str, usize)> {
+    let bom = if src.starts_with('\u{feff}') { 3 } else { 0 };
+    let mut lines = src[bom..].split_inclusive('\n');
+    let first = lines.next()?;
+    let (format, closing): (_, &[&str]) = match first.trim_end() {
+        "---" => (FrontMatterFormat::Yaml, &["---", "..."]),
+        "+++" => (FrontMatterFormat::Toml, &["+++"]),
+        _ => return None,
+    };
+    let raw_start = bom + first.len();
+    let mut offset = raw_start;
+    for line in lines {
+        if closing.contains(&line.trim_end()) {
+            return Some((format, &src[raw_start..offset], offset + line.len()));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// the top level values of the front matter,
+/// `None` if the feature of the format is not enabled or the front matter is invalid
+fn parse_values(
+    format: FrontMatterFormat,
+    raw: &str,
+) -> Option<BTreeMap<String, FrontMatterValue>> {
+    let values = match format {
+        FrontMatterFormat::Yaml => parse_yaml(raw)?,
+        FrontMatterFormat::Toml => parse_toml(raw)?,
+    };
+    match values {
+        Ok(FrontMatterValue::Map(values)) => Some(values),
+        Ok(_) => Some(BTreeMap::new()),
+        Err(e) => {
+            log::warn!("invalid front matter: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(feature = "front-matter-yaml")]
+fn parse_yaml(raw: &str) -> Option<Result<FrontMatterValue, String>> {
+    let values = serde_yaml::from_str::<serde_yaml::Value>(raw)
+        .map(FrontMatterValue::from)
+        .map_err(|e| e.to_string());
+    Some(values)
+}
+
+#[cfg(not(feature = "front-matter-yaml"))]
+fn parse_yaml(_raw: &str) -> Option<Result<FrontMatterValue, String>> {
+    None
+}
+
+#[cfg(feature = "front-matter-toml")]
+fn parse_toml(raw: &str) -> Option<Result<FrontMatterValue, String>> {
+    let values = raw
+        .parse::<toml::Value>()
+        .map(FrontMatterValue::from)
+        .map_err(|e| e.to_string());
+    Some(values)
+}
+
+#[cfg(not(feature = "front-matter-toml"))]
+fn parse_toml(_raw: &str) -> Option<Result<FrontMatterValue, String>> {
+    None
+}
+
+#[cfg(feature = "front-matter-yaml")]
+impl From<serde_yaml::Value> for FrontMatterValue {
+    fn from(value: serde_yaml::Value) -> Self {
+        use serde_yaml::Value;
+        match value {
+            Value::Null => FrontMatterValue::Null,
+            Value::Bool(v) => FrontMatterValue::Bool(v),
+            Value::Number(n) => match n.as_i64() {
+                Some(v) => FrontMatterValue::Integer(v),
+                None => FrontMatterValue::Float(n.as_f64().unwrap_or(f64::NAN)),
+            },
+            Value::String(v) => FrontMatterValue::String(v),
+            Value::Sequence(list) => {
+                FrontMatterValue::List(list.into_iter().map(FrontMatterValue::from).collect())
+            }
+            Value::Mapping(map) => FrontMatterValue::Map(
+                map.into_iter()
+                    .filter_map(|(key, value)| {
+                        let key = match key {
+                            Value::String(key) => key,
+                            Value::Number(key) => key.to_string(),
+                            Value::Bool(key) => key.to_string(),
+                            _ => return None,
+                        };
+                        Some((key, FrontMatterValue::from(value)))
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "front-matter-toml")]
+impl From<toml::Value> for FrontMatterValue {
+    fn from(value: toml::Value) -> Self {
+        use toml::Value;
+        match value {
+            Value::String(v) => FrontMatterValue::String(v),
+            Value::Integer(v) => FrontMatterValue::Integer(v),
+            Value::Float(v) => FrontMatterValue::Float(v),
+            Value::Boolean(v) => FrontMatterValue::Bool(v),
+            Value::Datetime(v) => FrontMatterValue::String(v.to_string()),
+            Value::Array(list) => {
+                FrontMatterValue::List(list.into_iter().map(FrontMatterValue::from).collect())
+            }
+            Value::Table(map) => FrontMatterValue::Map(
+                map.into_iter()
+                    .map(|(key, value)| (key, FrontMatterValue::from(value)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_front_matter() {
+        let src = "+++\ntitle = \"x\"\n+++\r\nbody";
+        let (format, raw, body_start) = split(src).expect("must split");
+        assert_eq!(format, FrontMatterFormat::Toml);
+        assert_eq!(raw, "title = \"x\"\n");
+        assert_eq!(&src[body_start..], "body");
+    }
+
+    #[test]
+    fn no_front_matter() {
+        assert_eq!(split("# title\n---\n"), None);
+        assert_eq!(split("---\nunclosed"), None);
+        assert_eq!(split("\n---\na: b\n---\n"), None);
+    }
+}
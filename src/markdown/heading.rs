@@ -0,0 +1,166 @@
+//! Generating the ids of the headings
+use pulldown_cmark::Event;
+use sauron::Node;
+use std::collections::BTreeSet;
+
+/// Creates the id of a heading from its text.
+///
+/// Closures with the signature `Fn(&str) -> String` can be used as a slugger.
+/// The slugs are deduplicated by the parser.
+pub trait Slugger {
+    /// create the slug of the heading text
+    fn slug(&self, text: &str) -> String;
+}
+
+impl<F> Slugger for F
+where
+    F: Fn(&str) -> String,
+{
+    fn slug(&self, text: &str) -> String {
+        self(text)
+    }
+}
+
+/// The slug algorithm used by GitHub.
+///
+/// The text is lowercased, punctuations are removed and spaces are replaced with `-`.
+/// ```rust
+/// use sauron_markdown::{GithubSlugger, Slugger};
+///
+/// assert_eq!(GithubSlugger.slug("Hello, World!"), "hello-world");
+/// assert_eq!(GithubSlugger.slug("Using `code` & more"), "using-code--more");
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GithubSlugger;
+
+impl Slugger for GithubSlugger {
+    fn slug(&self, text: &str) -> String {
+        text.trim()
+            .to_lowercase()
+            .chars()
+            .filter_map(|c| {
+                if c.is_alphanumeric() || c == '-' || c == '_' {
+                    Some(c)
+                } else if c == ' ' {
+                    Some('-')
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+/// Keeps track of the slugs used in the document,
+/// so each heading will have a unique id
+pub(crate) struct SlugRegistry {
+    used: BTreeSet<String>,
+}
+
+impl SlugRegistry {
+    pub(crate) fn new() -> Self {
+        Self {
+            used: BTreeSet::new(),
+        }
+    }
+
+    /// return the slug, or the slug with a `-1`, `-2`.. suffix if it is already used
+    pub(crate) fn unique(&mut self, slug: String) -> String {
+        let mut candidate = slug.clone();
+        let mut n = 0;
+        while self.used.contains(&candidate) {
+            n += 1;
+            candidate = format!("{}-{}", slug, n);
+        }
+        self.used.insert(candidate.clone());
+        candidate
+    }
+}
+
+/// the text content of the heading events
+pub(crate) fn heading_text(events: &[Event]) -> String {
+    events
+        .iter()
+        .filter_map(|ev| match ev {
+            Event::Text(content) | Event::Code(content) => Some(content.to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// the text content of the node and all of its descendants
+pub(crate) fn node_text<MSG>(node: &Node<MSG>) -> String {
+    match node.as_text() {
+        Some(content) => content.to_string(),
+        None => node.children().iter().map(node_text).collect(),
+    }
+}
+
+/// remove the `{#custom-id}` at the end of the heading events and return the id.
+/// The events are the `Start` and `End` of the heading and everything in between.
+pub(crate) fn take_custom_id(events: &mut Vec<Event>) -> Option<String> {
+    // the trailing text may be split into several text events
+    let end = events.len().checked_sub(1)?;
+    let mut start = end;
+    while start > 0 && matches!(events[start - 1], Event::Text(_)) {
+        start -= 1;
+    }
+    let trailing: String = events[start..end]
+        .iter()
+        .filter_map(|ev| match ev {
+            Event::Text(content) => Some(content.to_string()),
+            _ => None,
+        })
+        .collect();
+    let trailing = trailing.trim_end();
+    if !trailing.ends_with('}') {
+        return None;
+    }
+    let open = trailing.rfind("{#")?;
+    let id = &trailing[open + 2..trailing.len() - 1];
+    if id.is_empty() || id.contains(char::is_whitespace) {
+        return None;
+    }
+    let id = id.to_string();
+    let remaining = trailing[..open].trim_end().to_string();
+    let replacement = if remaining.is_empty() {
+        None
+    } else {
+        Some(Event::Text(remaining.into()))
+    };
+    events.splice(start..end, replacement);
+    Some(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pulldown_cmark::Tag;
+
+    #[test]
+    fn deduplicate_slugs() {
+        let mut slugs = SlugRegistry::new();
+        assert_eq!(slugs.unique("intro".to_string()), "intro");
+        assert_eq!(slugs.unique("intro".to_string()), "intro-1");
+        assert_eq!(slugs.unique("intro".to_string()), "intro-2");
+    }
+
+    #[test]
+    fn custom_id_split_across_text_events() {
+        let mut events = vec![
+            Event::Start(Tag::Heading(2)),
+            Event::Text("Title {#custom".into()),
+            Event::Text("-id}".into()),
+            Event::End(Tag::Heading(2)),
+        ];
+        assert_eq!(take_custom_id(&mut events), Some("custom-id".to_string()));
+        assert_eq!(
+            events,
+            vec![
+                Event::Start(Tag::Heading(2)),
+                Event::Text("Title".into()),
+                Event::End(Tag::Heading(2)),
+            ]
+        );
+    }
+}
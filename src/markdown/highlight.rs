@@ -0,0 +1,599 @@
+//! A small syntax highlighter for the fenced code blocks
+use sauron::*;
+use std::collections::BTreeMap;
+
+/// The kind of a token in the highlighted code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TokenKind {
+    /// keywords of the language, ie: `fn`, `let`
+    Keyword,
+    /// capitalized identifiers in languages where types are capitalized
+    Type,
+    /// literal values, ie: `true`, `null`
+    Literal,
+    /// strings and characters
+    String,
+    /// numbers
+    Number,
+    /// line and block comments
+    Comment,
+    /// everything else
+    Plain,
+}
+
+impl TokenKind {
+    /// the class used for the token, ie: `tok-keyword`
+    pub fn class_name(&self) -> &'static str {
+        match self {
+            TokenKind::Keyword => "tok-keyword",
+            TokenKind::Type => "tok-type",
+            TokenKind::Literal => "tok-literal",
+            TokenKind::String => "tok-string",
+            TokenKind::Number => "tok-number",
+            TokenKind::Comment => "tok-comment",
+            TokenKind::Plain => "tok-plain",
+        }
+    }
+}
+
+/// Inline styles of the tokens, used instead of the `tok-*` classes
+/// when the css for the classes is not available.
+/// ```rust
+/// use sauron_markdown::{HighlightTheme, TokenKind};
+///
+/// let theme = HighlightTheme::new().style(TokenKind::Keyword, "color:purple");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct HighlightTheme {
+    styles: BTreeMap<TokenKind, String>,
+}
+
+impl Default for HighlightTheme {
+    fn default() -> Self {
+        Self::empty()
+            .style(TokenKind::Keyword, "color:#d73a49")
+            .style(TokenKind::Type, "color:#6f42c1")
+            .style(TokenKind::Literal, "color:#005cc5")
+            .style(TokenKind::String, "color:#032f62")
+            .style(TokenKind::Number, "color:#005cc5")
+            .style(TokenKind::Comment, "color:#6a737d;font-style:italic")
+    }
+}
+
+impl HighlightTheme {
+    /// create the default theme, with colors similar to github
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// create a theme without styles
+    pub fn empty() -> Self {
+        Self {
+            styles: BTreeMap::new(),
+        }
+    }
+
+    /// use this css for the tokens of this kind
+    pub fn style(mut self, kind: TokenKind, css: impl ToString) -> Self {
+        self.styles.insert(kind, css.to_string());
+        self
+    }
+}
+
+/// The rules for tokenizing a language
+struct Language {
+    keywords: &'static [&'static str],
+    literals: &'static [&'static str],
+    line_comments: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+    quotes: &'static [char],
+    /// `'a'` is a character but `'a` is a lifetime
+    char_literals: bool,
+    capitalized_types: bool,
+}
+
+const RUST: Language = Language {
+    keywords: &[
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+        "extern", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut",
+        "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait", "type",
+        "unsafe", "use", "where", "while",
+    ],
+    literals: &["true", "false"],
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"'],
+    char_literals: true,
+    capitalized_types: true,
+};
+
+const JAVASCRIPT: Language = Language {
+    keywords: &[
+        "async",
+        "await",
+        "break",
+        "case",
+        "catch",
+        "class",
+        "const",
+        "continue",
+        "debugger",
+        "default",
+        "delete",
+        "do",
+        "else",
+        "enum",
+        "export",
+        "extends",
+        "finally",
+        "for",
+        "from",
+        "function",
+        "if",
+        "implements",
+        "import",
+        "in",
+        "instanceof",
+        "interface",
+        "let",
+        "new",
+        "of",
+        "private",
+        "protected",
+        "public",
+        "readonly",
+        "return",
+        "static",
+        "super",
+        "switch",
+        "this",
+        "throw",
+        "try",
+        "type",
+        "typeof",
+        "var",
+        "void",
+        "while",
+        "with",
+        "yield",
+    ],
+    literals: &["true", "false", "null", "undefined", "NaN", "Infinity"],
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"', '\'', '`'],
+    char_literals: false,
+    capitalized_types: true,
+};
+
+const PYTHON: Language = Language {
+    keywords: &[
+        "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del",
+        "elif", "else", "except", "finally", "for", "from", "global", "if", "import", "in", "is",
+        "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while", "with",
+        "yield",
+    ],
+    literals: &["True", "False", "None"],
+    line_comments: &["#"],
+    block_comment: None,
+    quotes: &['"', '\''],
+    char_literals: false,
+    capitalized_types: false,
+};
+
+const C: Language = Language {
+    keywords: &[
+        "auto",
+        "bool",
+        "break",
+        "case",
+        "char",
+        "class",
+        "const",
+        "continue",
+        "default",
+        "delete",
+        "do",
+        "double",
+        "else",
+        "enum",
+        "extern",
+        "float",
+        "for",
+        "goto",
+        "if",
+        "include",
+        "int",
+        "long",
+        "namespace",
+        "new",
+        "private",
+        "protected",
+        "public",
+        "register",
+        "return",
+        "short",
+        "signed",
+        "sizeof",
+        "static",
+        "struct",
+        "switch",
+        "template",
+        "this",
+        "typedef",
+        "typename",
+        "union",
+        "unsigned",
+        "using",
+        "virtual",
+        "void",
+        "volatile",
+        "while",
+    ],
+    literals: &["true", "false", "NULL", "nullptr"],
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"', '\''],
+    char_literals: false,
+    capitalized_types: false,
+};
+
+const GO: Language = Language {
+    keywords: &[
+        "break",
+        "case",
+        "chan",
+        "const",
+        "continue",
+        "default",
+        "defer",
+        "else",
+        "fallthrough",
+        "for",
+        "func",
+        "go",
+        "goto",
+        "if",
+        "import",
+        "interface",
+        "map",
+        "package",
+        "range",
+        "return",
+        "select",
+        "struct",
+        "switch",
+        "type",
+        "var",
+    ],
+    literals: &["true", "false", "nil", "iota"],
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"', '\'', '`'],
+    char_literals: false,
+    capitalized_types: false,
+};
+
+const JAVA: Language = Language {
+    keywords: &[
+        "abstract",
+        "boolean",
+        "break",
+        "byte",
+        "case",
+        "catch",
+        "char",
+        "class",
+        "continue",
+        "default",
+        "do",
+        "double",
+        "else",
+        "enum",
+        "extends",
+        "final",
+        "finally",
+        "float",
+        "for",
+        "if",
+        "implements",
+        "import",
+        "instanceof",
+        "int",
+        "interface",
+        "long",
+        "new",
+        "package",
+        "private",
+        "protected",
+        "public",
+        "return",
+        "short",
+        "static",
+        "super",
+        "switch",
+        "this",
+        "throw",
+        "throws",
+        "try",
+        "void",
+        "while",
+    ],
+    literals: &["true", "false", "null"],
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"', '\''],
+    char_literals: false,
+    capitalized_types: true,
+};
+
+const SHELL: Language = Language {
+    keywords: &[
+        "case", "do", "done", "elif", "else", "esac", "exit", "export", "fi", "for", "function",
+        "if", "in", "local", "return", "then", "while",
+    ],
+    literals: &["true", "false"],
+    line_comments: &["#"],
+    block_comment: None,
+    quotes: &['"', '\''],
+    char_literals: false,
+    capitalized_types: false,
+};
+
+const TOML: Language = Language {
+    keywords: &[],
+    literals: &["true", "false"],
+    line_comments: &["#"],
+    block_comment: None,
+    quotes: &['"', '\''],
+    char_literals: false,
+    capitalized_types: false,
+};
+
+const JSON: Language = Language {
+    keywords: &[],
+    literals: &["true", "false", "null"],
+    line_comments: &[],
+    block_comment: None,
+    quotes: &['"'],
+    char_literals: false,
+    capitalized_types: false,
+};
+
+fn language(name: &str) -> Option<&'static Language> {
+    let language = match name.to_lowercase().as_str() {
+        "rust" | "rs" => &RUST,
+        "javascript" | "js" | "jsx" | "typescript" | "ts" | "tsx" => &JAVASCRIPT,
+        "python" | "py" => &PYTHON,
+        "c" | "h" | "cpp" | "c++" | "cc" | "hpp" => &C,
+        "go" | "golang" => &GO,
+        "java" | "kotlin" => &JAVA,
+        "bash" | "sh" | "shell" | "zsh" => &SHELL,
+        "toml" => &TOML,
+        "json" => &JSON,
+        _ => return None,
+    };
+    Some(language)
+}
+
+/// split the code into tokens, `None` if the language is not supported
+pub(crate) fn tokenize<'a>(code: &'a str, lang: &str) -> Option<Vec<(TokenKind, &'a str)>> {
+    language(lang).map(|language| tokenize_with(code, language))
+}
+
+fn tokenize_with<'a>(code: &'a str, language: &Language) -> Vec<(TokenKind, &'a str)> {
+    let mut tokens: Vec<(TokenKind, &str)> = vec![];
+    let mut start = 0;
+    while let Some(ch) = code[start..].chars().next() {
+        let rest = &code[start..];
+        let (kind, len) = if language
+            .line_comments
+            .iter()
+            .any(|prefix| rest.starts_with(prefix))
+        {
+            (TokenKind::Comment, rest.find('\n').unwrap_or(rest.len()))
+        } else if let Some((open, close)) = language
+            .block_comment
+            .filter(|(open, _close)| rest.starts_with(open))
+        {
+            let len = rest[open.len()..]
+                .find(close)
+                .map(|end| open.len() + end + close.len())
+                .unwrap_or(rest.len());
+            (TokenKind::Comment, len)
+        } else if language.quotes.contains(&ch) {
+            (TokenKind::String, string_len(rest, ch))
+        } else if ch == '\'' && language.char_literals {
+            match char_literal_len(rest) {
+                Some(len) => (TokenKind::String, len),
+                None => (TokenKind::Plain, ch.len_utf8()),
+            }
+        } else if ch.is_ascii_digit() {
+            (TokenKind::Number, number_len(rest))
+        } else if ch.is_alphabetic() || ch == '_' {
+            let len = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            (classify(&rest[..len], language), len)
+        } else {
+            (TokenKind::Plain, ch.len_utf8())
+        };
+        match tokens.last_mut() {
+            // consecutive plain text are merged together
+            Some((TokenKind::Plain, last)) if kind == TokenKind::Plain => {
+                *last = &code[start - last.len()..start + len];
+            }
+            _ => tokens.push((kind, &rest[..len])),
+        }
+        start += len;
+    }
+    tokens
+}
+
+fn classify(word: &str, language: &Language) -> TokenKind {
+    if language.keywords.contains(&word) {
+        TokenKind::Keyword
+    } else if language.literals.contains(&word) {
+        TokenKind::Literal
+    } else if language.capitalized_types && word.starts_with(|c: char| c.is_uppercase()) {
+        TokenKind::Type
+    } else {
+        TokenKind::Plain
+    }
+}
+
+/// the length of the string including its quotes, escaped quotes are skipped
+fn string_len(rest: &str, quote: char) -> usize {
+    let mut escaped = false;
+    for (index, c) in rest.char_indices().skip(1) {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == quote {
+            return index + c.len_utf8();
+        }
+    }
+    rest.len()
+}
+
+/// the length of a character literal such as `'a'` or `'\n'`, `None` if it is a lifetime
+fn char_literal_len(rest: &str) -> Option<usize> {
+    let mut chars = rest.char_indices().skip(1);
+    let (_, first) = chars.next()?;
+    if first == '\\' {
+        // escapes such as `'\''` and `'\u{1F600}'`, the escaped character is skipped
+        let escaped = rest[2..].chars().next()?;
+        let start = 2 + escaped.len_utf8();
+        let end = rest[start..].find('\'').filter(|end| *end <= 8)?;
+        Some(start + end + 1)
+    } else {
+        match chars.next() {
+            Some((index, '\'')) => Some(index + 1),
+            _ => None,
+        }
+    }
+}
+
+/// the length of the number, a `.` is only part of the number when followed by a digit
+fn number_len(rest: &str) -> usize {
+    let bytes = rest.as_bytes();
+    let mut len = 0;
+    while len < bytes.len() {
+        let b = bytes[len];
+        let is_decimal_point = b == b'.'
+            && bytes
+                .get(len + 1)
+                .map_or(false, |next| next.is_ascii_digit());
+        if b.is_ascii_alphanumeric() || b == b'_' || is_decimal_point {
+            len += 1;
+        } else {
+            break;
+        }
+    }
+    len
+}
+
+/// the node of the token, plain text are not wrapped in a span
+pub(crate) fn token_node<MSG>(
+    kind: TokenKind,
+    content: &str,
+    theme: Option<&HighlightTheme>,
+) -> Node<MSG> {
+    if kind == TokenKind::Plain {
+        return text(content);
+    }
+    let attribute = match theme {
+        Some(theme) => match theme.styles.get(&kind) {
+            Some(css) => attr("style", css.to_string()),
+            None => empty_attr(),
+        },
+        None => class(kind.class_name()),
+    };
+    span([attribute], [text(content)])
+}
+
+/// the highlighted nodes of the code, `None` if the language is not supported
+pub(crate) fn highlight<MSG>(
+    code: &str,
+    lang: &str,
+    theme: Option<&HighlightTheme>,
+) -> Option<Vec<Node<MSG>>> {
+    tokenize(code, lang).map(|tokens| {
+        tokens
+            .into_iter()
+            .map(|(kind, content)| token_node(kind, content, theme))
+            .collect()
+    })
+}
+
+/// the highlighted nodes of each line of the code,
+/// the tokens which span multiple lines are split at the line breaks
+pub(crate) fn highlight_lines<MSG>(
+    code: &str,
+    lang: &str,
+    theme: Option<&HighlightTheme>,
+) -> Option<Vec<Vec<Node<MSG>>>> {
+    let tokens = tokenize(code, lang)?;
+    let mut lines: Vec<Vec<Node<MSG>>> = vec![vec![]];
+    for (kind, content) in tokens {
+        for part in content.split_inclusive('\n') {
+            if let Some(line) = lines.last_mut() {
+                line.push(token_node(kind, part, theme));
+            }
+            if part.ends_with('\n') {
+                lines.push(vec![]);
+            }
+        }
+    }
+    if lines.last().map(Vec::is_empty).unwrap_or(false) {
+        lines.pop();
+    }
+    Some(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokenize_rust() {
+        let tokens = tokenize("fn a<'b>(c: &'b str) -> char { 'x' } // done", "rust")
+            .expect("rust is supported");
+        let kinds: Vec<_> = tokens
+            .iter()
+            .filter(|(kind, _)| *kind != TokenKind::Plain)
+            .copied()
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (TokenKind::Keyword, "fn"),
+                (TokenKind::String, "'x'"),
+                (TokenKind::Comment, "// done"),
+            ]
+        );
+        let joined: String = tokens.iter().map(|(_, content)| *content).collect();
+        assert_eq!(joined, "fn a<'b>(c: &'b str) -> char { 'x' } // done");
+    }
+
+    #[test]
+    fn strings_and_numbers() {
+        let tokens = tokenize(r#"x = "a \" b" + 1.5 # note"#, "python").expect("supported");
+        assert!(tokens.contains(&(TokenKind::String, r#""a \" b""#)));
+        assert!(tokens.contains(&(TokenKind::Number, "1.5")));
+        assert!(tokens.contains(&(TokenKind::Comment, "# note")));
+        assert_eq!(tokenize("x", "brainfuck"), None);
+    }
+
+    #[test]
+    fn char_literals() {
+        for literal in &[r"'\''", r"'\n'", r"'\u{1F600}'", "'é'"] {
+            let tokens = tokenize(literal, "rust").expect("rust is supported");
+            assert_eq!(tokens, vec![(TokenKind::String, *literal)]);
+        }
+        // an unclosed escaped quote followed by a multibyte character
+        let tokens = tokenize(r"'\'é", "rust").expect("rust is supported");
+        let joined: String = tokens.iter().map(|(_, content)| *content).collect();
+        assert_eq!(joined, r"'\'é");
+    }
+}
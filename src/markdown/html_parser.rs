@@ -1,12 +1,13 @@
 //! An html parser used for parsing inline html used in markdown
 //!
+use crate::markdown::SanitizePolicy;
 use once_cell::sync::Lazy;
 use rphtml::config::ParseOptions;
 use rphtml::parser::Doc;
 use rphtml::parser::NodeType;
 use rphtml::types::BoxDynError;
 use sauron::html::attributes::Value;
-use sauron::html::html_element;
+use sauron::html::{html_element, node_list};
 use sauron::*;
 use sauron::{
     html::attributes::HTML_ATTRS_SPECIAL,
@@ -63,6 +64,37 @@ static ALL_ATTRS: Lazy<HashMap<&'static str, &'static str>> = Lazy::new(|| {
 static SELF_CLOSING_TAGS: Lazy<HashSet<&&'static str>> =
     Lazy::new(|| HashSet::from_iter(HTML_SC_TAGS.iter()));
 
+/// A single html tag in an html fragment, which needs to be matched
+/// with the rest of the markdown
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum LoneTag<'a> {
+    /// an opening tag without its closing tag, ie: `<details class="note">`
+    Open(&'a str),
+    /// a closing tag, ie: `</details>`
+    Close(&'a str),
+}
+
+/// return the tag name if the html is only an unclosed opening tag or a closing tag
+pub(crate) fn lone_tag(html: &str) -> Option<LoneTag<'_>> {
+    let html = html.trim();
+    if !html.starts_with('<') || !html.ends_with('>') || html[1..].contains('<') {
+        return None;
+    }
+    if let Some(rest) = html.strip_prefix("</") {
+        return Some(LoneTag::Close(rest.trim_end_matches('>').trim()));
+    }
+    if html.starts_with("<!") || html.starts_with("<?") || html.ends_with("/>") {
+        return None;
+    }
+    let name_end = html[1..].find(|c: char| c.is_whitespace() || c == '>' || c == '/')? + 1;
+    let tag = &html[1..name_end];
+    if is_self_closing(tag) {
+        None
+    } else {
+        Some(LoneTag::Open(tag))
+    }
+}
+
 /// return the matching attribute
 pub fn match_attribute(att: &str) -> Option<&'static str> {
     ALL_ATTRS
@@ -71,14 +103,6 @@ pub fn match_attribute(att: &str) -> Option<&'static str> {
         .map(|(_k, v)| *v)
 }
 
-/// given the attribute return the function name
-pub fn attribute_function(att: &str) -> Option<&'static str> {
-    ALL_ATTRS
-        .iter()
-        .find(|(_k, v)| v == &&att)
-        .map(|(k, _v)| *k)
-}
-
 /// return the matching tag
 pub fn match_tag(tag: &str) -> Option<&'static str> {
     ALL_HTML_TAGS
@@ -111,8 +135,11 @@ pub enum ParseError {
     InvalidTag(String),
 }
 
-/// the document is not wrapped with html
-pub fn parse_simple<MSG>(html: &str) -> Result<Option<Node<MSG>>, ParseError> {
+/// parse the html, removing the tags and attributes which are not allowed by the policy
+pub fn parse_with_policy<MSG>(
+    html: &str,
+    policy: Option<&SanitizePolicy>,
+) -> Result<Option<Node<MSG>>, ParseError> {
     let doc = Doc::parse(
         html,
         ParseOptions {
@@ -123,10 +150,13 @@ pub fn parse_simple<MSG>(html: &str) -> Result<Option<Node<MSG>>, ParseError> {
             auto_fix_unescaped_lt: true,
         },
     )?;
-    process_node(doc.get_root_node().borrow().deref())
+    process_node(doc.get_root_node().borrow().deref(), policy)
 }
 
-fn process_node<MSG>(node: &rphtml::parser::Node) -> Result<Option<Node<MSG>>, ParseError> {
+fn process_node<MSG>(
+    node: &rphtml::parser::Node,
+    policy: Option<&SanitizePolicy>,
+) -> Result<Option<Node<MSG>>, ParseError> {
     let content = if let Some(content) = &node.content {
         let content = String::from_iter(content.iter());
         Some(content)
@@ -137,7 +167,7 @@ fn process_node<MSG>(node: &rphtml::parser::Node) -> Result<Option<Node<MSG>>, P
     let mut child_nodes = if let Some(childs) = &node.childs {
         childs
             .iter()
-            .flat_map(|child| process_node(child.borrow().deref()).ok().flatten())
+            .flat_map(|child| process_node(child.borrow().deref(), policy).ok().flatten())
             .collect()
     } else {
         vec![]
@@ -146,10 +176,19 @@ fn process_node<MSG>(node: &rphtml::parser::Node) -> Result<Option<Node<MSG>>, P
     match node.node_type {
         NodeType::Tag => {
             let tag = &node.meta.as_ref().expect("must have a tag");
-            let tag_name = String::from_iter(tag.borrow().name.iter());
+            let tag_name = String::from_iter(tag.borrow().name.iter()).to_lowercase();
+            if let Some(policy) = policy {
+                if policy.is_clean_content_tag(&tag_name) {
+                    return Ok(None);
+                }
+                if !policy.is_tag_allowed(&tag_name) {
+                    // only the content of the disallowed tag is kept
+                    return Ok(Some(node_list(child_nodes)));
+                }
+            }
             if let Some(html_tag) = match_tag(&tag_name) {
                 let is_self_closing = HTML_SC_TAGS.contains(&html_tag);
-                let attributes: Vec<Attribute<MSG>> = tag
+                let mut attributes: Vec<Attribute<MSG>> = tag
                     .borrow()
                     .attrs
                     .iter()
@@ -158,9 +197,23 @@ fn process_node<MSG>(node: &rphtml::parser::Node) -> Result<Option<Node<MSG>>, P
                             .as_ref()
                             .map(|key| {
                                 let key = String::from_iter(key.content.iter());
+                                let value = attr
+                                    .value
+                                    .as_ref()
+                                    .map(|value| String::from_iter(value.content.iter()));
+                                if let Some(policy) = policy {
+                                    let value = value.as_deref().unwrap_or("");
+                                    if !policy.is_attribute_allowed(
+                                        html_tag,
+                                        &key.to_lowercase(),
+                                        value,
+                                    ) {
+                                        log::warn!("removed html attribute: {}", key);
+                                        return None;
+                                    }
+                                }
                                 if let Some(attr_key) = match_attribute(&key) {
-                                    let value = if let Some(value) = &attr.value {
-                                        let value = String::from_iter(value.content.iter());
+                                    let value = if let Some(value) = value {
                                         AttributeValue::Simple(Value::from(value))
                                     } else {
                                         AttributeValue::Empty
@@ -174,6 +227,13 @@ fn process_node<MSG>(node: &rphtml::parser::Node) -> Result<Option<Node<MSG>>, P
                             .flatten()
                     })
                     .collect();
+                if let Some(rel) = policy.and_then(|policy| policy.link_rel_for(html_tag)) {
+                    attributes.push(Attribute::new(
+                        None,
+                        "rel",
+                        AttributeValue::Simple(Value::from(rel.to_string())),
+                    ));
+                }
 
                 Ok(Some(html_element(
                     None,
@@ -189,14 +249,14 @@ fn process_node<MSG>(node: &rphtml::parser::Node) -> Result<Option<Node<MSG>>, P
         }
         NodeType::Text => {
             let content = content.expect("must have a content");
-            Ok(Some(text(content)))
+            Ok(Some(text(html_escape::decode_html_entities(&content))))
         }
         NodeType::AbstractRoot => {
             let child_nodes_len = child_nodes.len();
             match child_nodes_len {
                 0 => Ok(None),
                 1 => Ok(Some(child_nodes.remove(0))),
-                _ => Ok(Some(html_element(None, "html", vec![], child_nodes, false))),
+                _ => Ok(Some(node_list(child_nodes))),
             }
         }
         _ => Ok(None),
@@ -228,9 +288,20 @@ mod tests {
     </footer>
 </article>"#;
         let expected = "<article class=\"side-to-side\"><div>\n        This is div content1\n    </div><footer>\n        This is footer\n    </footer></article>";
-        let node: Node<()> = parse_simple(html).ok().flatten().expect("must parse");
+        let node: Node<()> = parse_with_policy(html, None)
+            .ok()
+            .flatten()
+            .expect("must parse");
         println!("node: {:#?}", node);
         println!("render: {}", node.render_to_string());
         assert_eq!(expected, node.render_to_string());
     }
+
+    #[test]
+    fn test_lone_tag() {
+        assert_eq!(Some(LoneTag::Open("details")), lone_tag("<details open>\n"));
+        assert_eq!(Some(LoneTag::Close("details")), lone_tag("</details>"));
+        assert_eq!(None, lone_tag("<br>"));
+        assert_eq!(None, lone_tag("<b>bold</b>"));
+    }
 }
@@ -0,0 +1,225 @@
+//! Re-parsing only the blocks of the markdown which were changed
+use crate::markdown::block_key::{content_hash, set_block_keys, set_span_keys, BlockKeys};
+use crate::markdown::{fallback, FrontMatter, MarkdownParser, MdParser};
+use pulldown_cmark::{Event, Parser, Tag};
+use sauron::Node;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::Range;
+
+/// A markdown document which caches the nodes of its top level blocks,
+/// so only the blocks touched by an edit are parsed again on update.
+///
+/// The top level nodes are given a `key` derived from the content of their block,
+/// so the nodes of the unchanged blocks are matched when sauron diffs the view.
+///
+/// The whole document is parsed again when it uses features which depends on the
+/// rest of the document: footnotes, heading ids, the `[TOC]` placeholder, source positions
+/// and the task list toggle.
+/// ```rust
+/// use sauron_markdown::{IncrementalDocument, MarkdownParser};
+///
+/// let mut document = IncrementalDocument::<()>::new(MarkdownParser::new());
+/// let nodes = document.update("# Title\n\nfirst paragraph");
+/// assert_eq!(nodes.len(), 2);
+/// let nodes = document.update("# Title\n\nedited paragraph");
+/// assert_eq!(nodes.len(), 2);
+/// assert_eq!(document.reparsed_blocks(), 1);
+/// ```
+pub struct IncrementalDocument<MSG> {
+    parser: MarkdownParser<MSG>,
+    /// the nodes of the blocks from the last update, keyed by the content hash
+    blocks: Vec<(u64, Vec<Node<MSG>>)>,
+    reparsed_blocks: usize,
+}
+
+impl<MSG> fmt::Debug for IncrementalDocument<MSG> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IncrementalDocument")
+            .field("parser", &self.parser)
+            .field("blocks", &self.blocks.len())
+            .field("reparsed_blocks", &self.reparsed_blocks)
+            .finish()
+    }
+}
+
+impl<MSG> Default for IncrementalDocument<MSG> {
+    fn default() -> Self {
+        Self::new(MarkdownParser::default())
+    }
+}
+
+impl<MSG> IncrementalDocument<MSG> {
+    /// create an empty document which uses this parser
+    pub fn new(parser: MarkdownParser<MSG>) -> Self {
+        Self {
+            parser,
+            blocks: vec![],
+            reparsed_blocks: 0,
+        }
+    }
+
+    /// the number of blocks which were parsed in the last update
+    pub fn reparsed_blocks(&self) -> usize {
+        self.reparsed_blocks
+    }
+
+    /// parse the changed blocks of the markdown and return the nodes of the whole document
+    pub fn update(&mut self, src: &str) -> Vec<Node<MSG>>
+    where
+        Node<MSG>: Clone,
+    {
+        let options = &self.parser.options;
+        let body = if options.front_matter {
+            FrontMatter::extract(src).1
+        } else {
+            src
+        };
+        let needs_whole_document = options.heading_ids
+            || options.toc_placeholder
+            || options.source_positions
+            || self.parser.task_toggle.is_some();
+        match top_level_blocks(body, &self.parser) {
+            Some(blocks) if !needs_whole_document => self.update_blocks(body, blocks),
+            _ => self.update_whole(src),
+        }
+    }
+
+    fn update_blocks(&mut self, body: &str, blocks: Vec<Range<usize>>) -> Vec<Node<MSG>>
+    where
+        Node<MSG>: Clone,
+    {
+        // the link reference definitions are not part of any block,
+        // they are added to every block so the links can be resolved
+        let definitions = definitions(body, &blocks);
+        let mut cached: BTreeMap<u64, Vec<Vec<Node<MSG>>>> = BTreeMap::new();
+        for (hash, nodes) in std::mem::take(&mut self.blocks).into_iter().rev() {
+            cached.entry(hash).or_default().push(nodes);
+        }
+        self.reparsed_blocks = 0;
+        let mut keys = BlockKeys::new();
+        let mut document = vec![];
+        for range in blocks {
+            let block = format!("{}\n\n{}", &body[range], definitions);
+            let hash = content_hash(&block);
+            let nodes = match cached.get_mut(&hash).and_then(|nodes| nodes.pop()) {
+                Some(nodes) => nodes,
+                None => {
+                    self.reparsed_blocks += 1;
+                    self.parse_block(&block)
+                }
+            };
+            let mut keyed = nodes.clone();
+            set_block_keys(&mut keyed, &keys.next(hash));
+            document.extend(keyed);
+            self.blocks.push((hash, nodes));
+        }
+        document
+    }
+
+    /// parse the markdown as a whole, the cache is cleared
+    fn update_whole(&mut self, src: &str) -> Vec<Node<MSG>> {
+        self.blocks.clear();
+        let (mut nodes, spans) = self.parser.parse_with_spans(src);
+        self.reparsed_blocks = nodes.len();
+        // the parser already added the keys when `block_keys` is enabled
+        if !self.parser.options.block_keys {
+            set_span_keys(src, &mut nodes, &spans);
+        }
+        nodes
+    }
+
+    /// parse the block, the front matter is not detected in the block
+    fn parse_block(&self, block: &str) -> Vec<Node<MSG>> {
+        MdParser::new(&self.parser)
+            .parse(block, 0)
+            .map(|(_toc, nodes, _spans, _footnotes)| self.parser.apply_transforms(nodes))
+            .unwrap_or_else(|e| fallback(block, e))
+    }
+}
+
+/// the byte ranges of the top level blocks of the markdown,
+/// or `None` if the blocks can not be parsed separately such as when the markdown has footnotes.
+/// Consecutive html are grouped together since they are parsed together,
+/// and so are the blocks in between an unclosed html tag and its closing tag,
+/// and the blocks of the indented content of a `!!!` admonition.
+fn top_level_blocks<MSG>(body: &str, parser: &MarkdownParser<MSG>) -> Option<Vec<Range<usize>>> {
+    let mut blocks: Vec<Range<usize>> = vec![];
+    let mut depth = 0;
+    // the number of html tags which are not yet closed
+    #[allow(unused_mut)]
+    let mut open_html = 0;
+    let mut previous_html = false;
+    // where the content of the `!!!` admonition being grouped ends
+    let mut admonition_end = 0;
+    let events = Parser::new_ext(body, parser.options.pulldown_options()).into_offset_iter();
+    for (ev, range) in events {
+        if matches!(
+            ev,
+            Event::FootnoteReference(_) | Event::Start(Tag::FootnoteDefinition(_))
+        ) {
+            return None;
+        }
+        if depth == 0 {
+            let is_html = matches!(ev, Event::Html(_));
+            match blocks.last_mut() {
+                Some(last)
+                    if open_html > 0
+                        || (is_html && previous_html)
+                        || range.start < admonition_end =>
+                {
+                    last.end = last.end.max(range.end)
+                }
+                _ => {
+                    if let (Event::Start(Tag::Paragraph), Some(admonitions)) =
+                        (&ev, parser.options.admonitions.as_ref())
+                    {
+                        if let Some((_header, content)) = admonitions.fence_block(body, range.start)
+                        {
+                            admonition_end = content.end;
+                        }
+                    }
+                    blocks.push(range)
+                }
+            }
+            previous_html = is_html;
+            #[cfg(feature = "parse-html")]
+            {
+                use crate::markdown::html_parser::{lone_tag, LoneTag};
+                if let Event::Html(html) = &ev {
+                    match lone_tag(html) {
+                        Some(LoneTag::Open(_)) => open_html += 1,
+                        Some(LoneTag::Close(_)) => open_html = open_html.saturating_sub(1),
+                        None => (),
+                    }
+                }
+            }
+        }
+        match ev {
+            Event::Start(_) => depth += 1,
+            Event::End(_) => depth -= 1,
+            _ => (),
+        }
+    }
+    Some(blocks)
+}
+
+/// the text in between the blocks which are not blank
+pub(crate) fn definitions(body: &str, blocks: &[Range<usize>]) -> String {
+    let mut definitions = String::new();
+    let mut start = 0;
+    for block in blocks {
+        push_definition(&mut definitions, &body[start..block.start]);
+        start = block.end;
+    }
+    push_definition(&mut definitions, &body[start..]);
+    definitions
+}
+
+fn push_definition(definitions: &mut String, gap: &str) {
+    let gap = gap.trim();
+    if !gap.is_empty() {
+        definitions.push_str(gap);
+        definitions.push('\n');
+    }
+}
@@ -0,0 +1,51 @@
+//! Information about the links in the markdown, used for intercepting the clicks on the links
+use crate::markdown::url_policy::url_scheme;
+
+/// The markdown link which was clicked
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkInfo {
+    /// the destination of the link, as written in the markdown.
+    /// Only the links whose url is allowed by the [`UrlPolicy`](crate::UrlPolicy) are reported
+    pub url: String,
+    /// the title of the link, ie: `title` in `[text](url "title")`
+    pub title: String,
+    /// whether the link is within the site, such as relative urls and fragments
+    pub is_internal: bool,
+}
+
+impl LinkInfo {
+    pub(crate) fn new(url: &str, title: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            title: title.to_string(),
+            is_internal: is_internal(url),
+        }
+    }
+}
+
+/// urls without a scheme are internal, except for protocol relative urls such as `//example.com`.
+/// Browsers treat a `\\` as a `/` in the urls, so `/\\example.com` is protocol relative too
+fn is_internal(url: &str) -> bool {
+    let is_slash = |c: char| c == '/' || c == '\\';
+    let mut chars = url.trim_start().chars();
+    let protocol_relative =
+        chars.next().map_or(false, is_slash) && chars.next().map_or(false, is_slash);
+    url_scheme(url).is_none() && !protocol_relative
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn internal_links() {
+        assert!(is_internal("/docs/intro"));
+        assert!(is_internal("../intro.md"));
+        assert!(is_internal("#usage"));
+        assert!(!is_internal("https://example.com"));
+        assert!(!is_internal("//example.com"));
+        assert!(!is_internal("/\\example.com"));
+        assert!(!is_internal("\\\\example.com"));
+        assert!(!is_internal("mailto:someone@example.com"));
+    }
+}
@@ -0,0 +1,414 @@
+//! Recognizing the inline `$...$` and display `$$...$$` math in the text of the markdown.
+//!
+//! The math is converted into `math` code blocks, so it is rendered by the processor
+//! registered to the `math` language, the same as the ```` ```math ```` fenced code blocks.
+#[cfg(feature = "mathml")]
+use crate::markdown::mathml;
+use crate::markdown::{CodeFenceInfo, CodeFenceProcessor};
+use pulldown_cmark::{CodeBlockKind, CowStr, Event, Tag};
+use sauron::*;
+use std::collections::VecDeque;
+use std::ops::Range;
+
+/// the info string of the code blocks created from the inline math
+static INLINE_INFO: &str = "math inline";
+/// the info string of the code blocks created from the display math
+static DISPLAY_INFO: &str = "math display";
+/// the info string of the code blocks created from the display math which is a part of a
+/// paragraph, it is rendered as a `<span>` since a `<div>` can not be inside a `<p>`
+static PARAGRAPH_DISPLAY_INFO: &str = "math display in-paragraph";
+
+/// whether the info string is of a code block created from the `$` or `$$` math.
+///
+/// The info string is compared by its address instead of its content,
+/// so a ```` ```math inline ```` fenced code block is not mistaken for the math.
+pub(crate) fn is_math_span(info: &str) -> bool {
+    [INLINE_INFO, DISPLAY_INFO, PARAGRAPH_DISPLAY_INFO]
+        .iter()
+        .any(|math_info| std::ptr::eq(info, *math_info))
+}
+
+/// Converts the math in the text events into `math` code blocks.
+///
+/// The math is taken from the source instead of the text of the events,
+/// so the backslashes and the emphasis characters in the TeX are kept as is.
+/// Escaped dollar signs and the text inside code spans and code blocks are not math.
+pub(crate) struct MathEvents<'a, I> {
+    body: &'a str,
+    events: I,
+    /// the events which are read ahead while looking for the closing delimiter
+    /// and were not part of the math
+    pending: VecDeque<(Event<'a>, Range<usize>)>,
+    output: VecDeque<(Event<'a>, Range<usize>)>,
+    in_code_block: bool,
+}
+
+impl<'a, I> MathEvents<'a, I>
+where
+    I: Iterator<Item = (Event<'a>, Range<usize>)>,
+{
+    pub(crate) fn new(body: &'a str, events: I) -> Self {
+        Self {
+            body,
+            events,
+            pending: VecDeque::new(),
+            output: VecDeque::new(),
+            in_code_block: false,
+        }
+    }
+
+    fn next_input(&mut self) -> Option<(Event<'a>, Range<usize>)> {
+        self.pending.pop_front().or_else(|| self.events.next())
+    }
+
+    /// whether the text is the same as its source, such that it is not an escape or an entity
+    fn is_plain(&self, text: &str, range: &Range<usize>) -> bool {
+        self.body.get(range.clone()) == Some(text)
+    }
+
+    fn process_text(&mut self, text: CowStr<'a>, range: Range<usize>) {
+        let body = self.body;
+        let (open, display) = match find_opening(body, range.clone()) {
+            Some(opening) => opening,
+            None => {
+                self.output.push_back((Event::Text(text), range));
+                return;
+            }
+        };
+        let delimiter = if display { 2 } else { 1 };
+        let content_start = open + delimiter;
+        let mut close = find_closing(body, content_start..range.end, display);
+        let mut close_end = range.end;
+        // the events read ahead, the math can span several events such as soft breaks
+        let mut read = vec![];
+        let mut depth = 0;
+        while close.is_none() {
+            let (ev, ev_range) = match self.next_input() {
+                Some(input) => input,
+                None => break,
+            };
+            match &ev {
+                Event::Start(_) => depth += 1,
+                Event::End(_) => depth -= 1,
+                Event::Text(text) if depth == 0 && self.is_plain(text, &ev_range) => {
+                    close = find_closing(body, ev_range.clone(), display);
+                    close_end = ev_range.end;
+                }
+                _ => (),
+            }
+            read.push((ev, ev_range));
+            // the element containing the opening delimiter has ended
+            if depth < 0 {
+                break;
+            }
+        }
+
+        match close {
+            Some(close) => {
+                if open > range.start {
+                    self.push_text(range.start..open);
+                }
+                let tex = self.math_source(content_start..close, &read);
+                let math_range = open..close + delimiter;
+                let rest = close + delimiter..close_end;
+                let (info, tex) = if !display {
+                    (INLINE_INFO, tex)
+                } else if open == range.start && rest.is_empty() && self.unwrap_paragraph() {
+                    (DISPLAY_INFO, tex.trim().to_string())
+                } else {
+                    (PARAGRAPH_DISPLAY_INFO, tex.trim().to_string())
+                };
+                let kind = || Tag::CodeBlock(CodeBlockKind::Fenced(CowStr::Borrowed(info)));
+                self.output
+                    .push_back((Event::Start(kind()), math_range.clone()));
+                self.output
+                    .push_back((Event::Text(tex.into()), math_range.clone()));
+                self.output.push_back((Event::End(kind()), math_range));
+                // the text after the closing delimiter may contain more math
+                if !rest.is_empty() {
+                    self.pending
+                        .push_front((Event::Text(CowStr::Borrowed(&body[rest.clone()])), rest));
+                }
+            }
+            None => {
+                // not math, the opening delimiter is kept as text and the rest is scanned again
+                for input in read.into_iter().rev() {
+                    self.pending.push_front(input);
+                }
+                let rest = content_start..range.end;
+                if !rest.is_empty() {
+                    self.pending
+                        .push_front((Event::Text(CowStr::Borrowed(&body[rest.clone()])), rest));
+                }
+                self.push_text(range.start..content_start);
+            }
+        }
+    }
+
+    /// remove the paragraph around the display math when it is the only content,
+    /// returns whether the paragraph was removed
+    fn unwrap_paragraph(&mut self) -> bool {
+        if !matches!(self.output.back(), Some((Event::Start(Tag::Paragraph), _))) {
+            return false;
+        }
+        match self.next_input() {
+            Some((Event::End(Tag::Paragraph), _)) => {
+                self.output.pop_back();
+                true
+            }
+            Some(input) => {
+                self.pending.push_front(input);
+                false
+            }
+            None => false,
+        }
+    }
+
+    fn push_text(&mut self, range: Range<usize>) {
+        let text = CowStr::Borrowed(&self.body[range.clone()]);
+        self.output.push_back((Event::Text(text), range));
+    }
+
+    /// the source of the math, without the prefixes of the lines such as `> ` of the
+    /// blockquotes or the indentation of the list items
+    fn math_source(&self, content: Range<usize>, read: &[(Event<'a>, Range<usize>)]) -> String {
+        let mut tex = String::new();
+        let mut cursor = content.start;
+        let mut after_break = false;
+        for (ev, range) in read {
+            if matches!(ev, Event::Start(_) | Event::End(_)) {
+                continue;
+            }
+            let start = range.start.max(cursor);
+            let end = range.end.min(content.end).max(start);
+            if !after_break {
+                tex.push_str(&self.body[cursor..start]);
+            }
+            tex.push_str(&self.body[start..end]);
+            cursor = end;
+            after_break = matches!(ev, Event::SoftBreak | Event::HardBreak);
+        }
+        if !after_break {
+            tex.push_str(&self.body[cursor..content.end]);
+        }
+        tex
+    }
+}
+
+impl<'a, I> Iterator for MathEvents<'a, I>
+where
+    I: Iterator<Item = (Event<'a>, Range<usize>)>,
+{
+    type Item = (Event<'a>, Range<usize>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // the start of a paragraph is held until its content is processed,
+        // in case it only contains display math
+        while self.output.is_empty()
+            || matches!(self.output.back(), Some((Event::Start(Tag::Paragraph), _)))
+        {
+            let (ev, range) = match self.next_input() {
+                Some(input) => input,
+                None => break,
+            };
+            match ev {
+                Event::Text(text) if !self.in_code_block && self.is_plain(&text, &range) => {
+                    self.process_text(text, range)
+                }
+                ev => {
+                    match &ev {
+                        Event::Start(Tag::CodeBlock(_)) => self.in_code_block = true,
+                        Event::End(Tag::CodeBlock(_)) => self.in_code_block = false,
+                        _ => (),
+                    }
+                    self.output.push_back((ev, range));
+                }
+            }
+        }
+        self.output.pop_front()
+    }
+}
+
+/// find the opening `$` or `$$` in this range of the source,
+/// the inline `$` must be followed by a non-whitespace character
+fn find_opening(body: &str, range: Range<usize>) -> Option<(usize, bool)> {
+    let mut from = range.start;
+    while let Some(offset) = body[from..range.end].find('$') {
+        let pos = from + offset;
+        from = pos + 1;
+        if is_escaped(body, pos) {
+            continue;
+        }
+        let rest = &body[pos + 1..];
+        if rest.starts_with('$') {
+            return Some((pos, true));
+        }
+        if rest.starts_with(|c: char| !c.is_whitespace()) {
+            return Some((pos, false));
+        }
+    }
+    None
+}
+
+/// find the closing `$` or `$$` in this range of the source,
+/// the inline `$` must not be preceded by a whitespace nor followed by a digit,
+/// so prices such as `$5 and $10` are not math
+fn find_closing(body: &str, range: Range<usize>, display: bool) -> Option<usize> {
+    let delimiter = if display { "$$" } else { "$" };
+    let mut from = range.start;
+    while let Some(offset) = body[from..range.end].find(delimiter) {
+        let pos = from + offset;
+        from = pos + 1;
+        if is_escaped(body, pos) {
+            continue;
+        }
+        if display {
+            return Some(pos);
+        }
+        let after_space = body[..pos].ends_with(char::is_whitespace);
+        let before_digit = body[pos + 1..].starts_with(|c: char| c.is_ascii_digit());
+        if !after_space && !before_digit {
+            return Some(pos);
+        }
+    }
+    None
+}
+
+/// whether the character at this position is escaped by an odd number of backslashes
+fn is_escaped(body: &str, pos: usize) -> bool {
+    body[..pos]
+        .bytes()
+        .rev()
+        .take_while(|b| *b == b'\\')
+        .count()
+        % 2
+        == 1
+}
+
+/// Renders the `math` code blocks into `<span class="math inline">` or
+/// `<div class="math display">`, or `<span class="math display">` inside a paragraph,
+/// containing the TeX source
+/// or the MathML when the `mathml` feature is enabled.
+pub(crate) struct MathProcessor {
+    #[cfg(feature = "mathml")]
+    mathml: bool,
+}
+
+impl MathProcessor {
+    pub(crate) fn new(#[cfg(feature = "mathml")] mathml: bool) -> Self {
+        Self {
+            #[cfg(feature = "mathml")]
+            mathml,
+        }
+    }
+
+    #[cfg(feature = "mathml")]
+    fn content<MSG>(&self, tex: &str, display: bool) -> Node<MSG> {
+        if self.mathml {
+            mathml::to_mathml(tex, display)
+        } else {
+            text(tex)
+        }
+    }
+
+    #[cfg(not(feature = "mathml"))]
+    fn content<MSG>(&self, tex: &str, _display: bool) -> Node<MSG> {
+        text(tex)
+    }
+}
+
+impl<MSG> CodeFenceProcessor<MSG> for MathProcessor {
+    fn process(&self, info: &CodeFenceInfo, tex: &str) -> Option<Node<MSG>> {
+        let display = !has_flag(info, "inline");
+        Some(math_element(info, self.content(tex.trim(), display)))
+    }
+}
+
+/// the math element with the TeX source, used when the processor registered to the `math`
+/// language does not render the code block created from the math
+pub(crate) fn tex_element<MSG>(info: &CodeFenceInfo, tex: &str) -> Node<MSG> {
+    math_element(info, text(tex.trim()))
+}
+
+/// wrap the content of the math in the element of its flags
+fn math_element<MSG>(info: &CodeFenceInfo, content: Node<MSG>) -> Node<MSG> {
+    if has_flag(info, "inline") {
+        span([class("math inline")], [content])
+    } else if has_flag(info, "in-paragraph") {
+        span([class("math display")], [content])
+    } else {
+        div([class("math display")], [content])
+    }
+}
+
+fn has_flag(info: &CodeFenceInfo, name: &str) -> bool {
+    info.flags.iter().any(|flag| flag == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pulldown_cmark::Parser;
+
+    fn math_events(md: &str) -> Vec<Event> {
+        MathEvents::new(md, Parser::new(md).into_offset_iter())
+            .map(|(ev, _range)| ev)
+            .collect()
+    }
+
+    fn inline_math(tex: &str) -> Vec<Event> {
+        let kind = || Tag::CodeBlock(CodeBlockKind::Fenced(INLINE_INFO.into()));
+        vec![
+            Event::Start(kind()),
+            Event::Text(tex.into()),
+            Event::End(kind()),
+        ]
+    }
+
+    #[test]
+    fn inline() {
+        let mut expected = vec![Event::Start(Tag::Paragraph), Event::Text("area ".into())];
+        expected.extend(inline_math(r"\pi r^2"));
+        expected.extend(vec![Event::Text(".".into()), Event::End(Tag::Paragraph)]);
+        assert_eq!(expected, math_events(r"area $\pi r^2$."));
+    }
+
+    #[test]
+    fn tex_is_taken_from_the_source() {
+        let events = math_events(r"$a *b* \{c\}$");
+        assert_eq!(&events[1..4], &inline_math(r"a *b* \{c\}")[..]);
+    }
+
+    #[test]
+    fn display_in_blockquote() {
+        let events = math_events("> $$\n> x^2\n> $$");
+        let kind = || Tag::CodeBlock(CodeBlockKind::Fenced(DISPLAY_INFO.into()));
+        // the paragraph around the display math is removed
+        assert_eq!(
+            &events[1..4],
+            &[
+                Event::Start(kind()),
+                Event::Text("x^2".into()),
+                Event::End(kind())
+            ]
+        );
+    }
+
+    #[test]
+    fn not_math() {
+        let text = |md| -> String {
+            math_events(md)
+                .into_iter()
+                .filter_map(|ev| match ev {
+                    Event::Text(text) => Some(text.to_string()),
+                    Event::Code(code) => Some(format!("`{}`", code)),
+                    _ => None,
+                })
+                .collect()
+        };
+        assert_eq!("costs $5 and $10", text("costs $5 and $10"));
+        assert_eq!("$x$", text(r"\$x$"));
+        assert_eq!("`$y$`", text("`$y$`"));
+        assert_eq!("$ x $", text("$ x $"));
+    }
+}
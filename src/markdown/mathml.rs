@@ -0,0 +1,637 @@
+//! Converts a subset of TeX into MathML nodes
+use sauron::html::html_element;
+use sauron::*;
+
+const MATHML_NAMESPACE: &str = "http://www.w3.org/1998/Math/MathML";
+
+/// the maximum nesting of the groups and the commands,
+/// the TeX which is nested deeper is shown as is instead of overflowing the stack
+const MAX_DEPTH: usize = 64;
+
+/// the commands which are rendered as identifiers
+const IDENTIFIERS: [(&str, &str); 46] = [
+    ("alpha", "α"),
+    ("beta", "β"),
+    ("gamma", "γ"),
+    ("delta", "δ"),
+    ("epsilon", "ϵ"),
+    ("varepsilon", "ε"),
+    ("zeta", "ζ"),
+    ("eta", "η"),
+    ("theta", "θ"),
+    ("vartheta", "ϑ"),
+    ("iota", "ι"),
+    ("kappa", "κ"),
+    ("lambda", "λ"),
+    ("mu", "μ"),
+    ("nu", "ν"),
+    ("xi", "ξ"),
+    ("pi", "π"),
+    ("varpi", "ϖ"),
+    ("rho", "ρ"),
+    ("varrho", "ϱ"),
+    ("sigma", "σ"),
+    ("varsigma", "ς"),
+    ("tau", "τ"),
+    ("upsilon", "υ"),
+    ("phi", "ϕ"),
+    ("varphi", "φ"),
+    ("chi", "χ"),
+    ("psi", "ψ"),
+    ("omega", "ω"),
+    ("Gamma", "Γ"),
+    ("Delta", "Δ"),
+    ("Theta", "Θ"),
+    ("Lambda", "Λ"),
+    ("Xi", "Ξ"),
+    ("Pi", "Π"),
+    ("Sigma", "Σ"),
+    ("Upsilon", "Υ"),
+    ("Phi", "Φ"),
+    ("Psi", "Ψ"),
+    ("Omega", "Ω"),
+    ("infty", "∞"),
+    ("partial", "∂"),
+    ("nabla", "∇"),
+    ("ell", "ℓ"),
+    ("hbar", "ℏ"),
+    ("emptyset", "∅"),
+];
+
+/// the commands which are rendered as operators
+const OPERATORS: [(&str, &str); 52] = [
+    ("times", "×"),
+    ("cdot", "⋅"),
+    ("div", "÷"),
+    ("pm", "±"),
+    ("mp", "∓"),
+    ("ast", "∗"),
+    ("circ", "∘"),
+    ("leq", "≤"),
+    ("le", "≤"),
+    ("geq", "≥"),
+    ("ge", "≥"),
+    ("neq", "≠"),
+    ("ne", "≠"),
+    ("approx", "≈"),
+    ("equiv", "≡"),
+    ("sim", "∼"),
+    ("simeq", "≃"),
+    ("propto", "∝"),
+    ("ll", "≪"),
+    ("gg", "≫"),
+    ("in", "∈"),
+    ("notin", "∉"),
+    ("subset", "⊂"),
+    ("subseteq", "⊆"),
+    ("supset", "⊃"),
+    ("supseteq", "⊇"),
+    ("cup", "∪"),
+    ("cap", "∩"),
+    ("setminus", "∖"),
+    ("forall", "∀"),
+    ("exists", "∃"),
+    ("neg", "¬"),
+    ("land", "∧"),
+    ("lor", "∨"),
+    ("to", "→"),
+    ("rightarrow", "→"),
+    ("leftarrow", "←"),
+    ("leftrightarrow", "↔"),
+    ("Rightarrow", "⇒"),
+    ("Leftarrow", "⇐"),
+    ("Leftrightarrow", "⇔"),
+    ("mapsto", "↦"),
+    ("sum", "∑"),
+    ("prod", "∏"),
+    ("int", "∫"),
+    ("iint", "∬"),
+    ("oint", "∮"),
+    ("bigcup", "⋃"),
+    ("bigcap", "⋂"),
+    ("cdots", "⋯"),
+    ("ldots", "…"),
+    ("dots", "…"),
+];
+
+/// the functions which are rendered as upright identifiers, ie: `\sin`
+const FUNCTIONS: [&str; 26] = [
+    "arccos", "arcsin", "arctan", "arg", "cos", "cosh", "cot", "csc", "deg", "det", "dim", "exp",
+    "gcd", "inf", "ker", "lg", "lim", "ln", "log", "max", "min", "Pr", "sec", "sin", "sup", "tan",
+];
+
+/// the commands which put an accent over their argument
+const ACCENTS: [(&str, &str); 7] = [
+    ("hat", "^"),
+    ("bar", "¯"),
+    ("overline", "¯"),
+    ("vec", "→"),
+    ("dot", "˙"),
+    ("ddot", "¨"),
+    ("tilde", "~"),
+];
+
+/// the commands which changes the font of their argument
+const FONTS: [(&str, &str); 8] = [
+    ("mathbf", "bold"),
+    ("boldsymbol", "bold-italic"),
+    ("mathit", "italic"),
+    ("mathbb", "double-struck"),
+    ("mathcal", "script"),
+    ("mathfrak", "fraktur"),
+    ("mathsf", "sans-serif"),
+    ("mathtt", "monospace"),
+];
+
+/// the spacing commands and their width
+const SPACES: [(&str, &str); 6] = [
+    (",", "0.1667em"),
+    (":", "0.2222em"),
+    (">", "0.2222em"),
+    (";", "0.2778em"),
+    ("quad", "1em"),
+    ("qquad", "2em"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token<'a> {
+    /// `\name` or an escaped character such as `\{`
+    Command(&'a str),
+    Open,
+    Close,
+    Superscript,
+    Subscript,
+    /// `&`, the column separator
+    Align,
+    /// `\\`, the row separator
+    NewRow,
+    Space,
+    Number(&'a str),
+    Letter(char),
+    Symbol(char),
+}
+
+fn tokenize(tex: &str) -> Vec<Token> {
+    let mut tokens = vec![];
+    let mut chars = tex.char_indices().peekable();
+    while let Some((index, ch)) = chars.next() {
+        let token = match ch {
+            '\\' => match chars.next() {
+                Some((_, '\\')) => Token::NewRow,
+                Some((start, c)) if c.is_ascii_alphabetic() => {
+                    let mut end = start + 1;
+                    while let Some((i, _c)) = chars.next_if(|(_i, c)| c.is_ascii_alphabetic()) {
+                        end = i + 1;
+                    }
+                    Token::Command(&tex[start..end])
+                }
+                Some((start, c)) => Token::Command(&tex[start..start + c.len_utf8()]),
+                None => Token::Symbol('\\'),
+            },
+            '{' => Token::Open,
+            '}' => Token::Close,
+            '^' => Token::Superscript,
+            '_' => Token::Subscript,
+            '&' => Token::Align,
+            c if c.is_whitespace() => Token::Space,
+            c if c.is_ascii_digit() => {
+                let mut end = index + 1;
+                while let Some((i, c)) = chars.next_if(|(_i, c)| c.is_ascii_digit() || *c == '.') {
+                    end = i + c.len_utf8();
+                }
+                Token::Number(&tex[index..end])
+            }
+            c if c.is_alphabetic() => Token::Letter(c),
+            c => Token::Symbol(c),
+        };
+        tokens.push(token);
+    }
+    tokens
+}
+
+fn element<MSG>(
+    tag: &'static str,
+    attributes: impl IntoIterator<Item = Attribute<MSG>>,
+    children: impl IntoIterator<Item = Node<MSG>>,
+) -> Node<MSG> {
+    html_element(Some(MATHML_NAMESPACE), tag, attributes, children, false)
+}
+
+fn mi<MSG>(content: impl ToString) -> Node<MSG> {
+    element("mi", [], [text(content)])
+}
+
+fn mo<MSG>(content: impl ToString) -> Node<MSG> {
+    element("mo", [], [text(content)])
+}
+
+/// wrap the nodes in a `mrow`, unless there is only one node
+fn row<MSG>(mut nodes: Vec<Node<MSG>>) -> Node<MSG> {
+    if nodes.len() == 1 {
+        nodes.remove(0)
+    } else {
+        element("mrow", [], nodes)
+    }
+}
+
+fn lookup<'a>(table: &[(&str, &'a str)], name: &str) -> Option<&'a str> {
+    table
+        .iter()
+        .find(|(command, _value)| *command == name)
+        .map(|(_command, value)| *value)
+}
+
+/// the delimiter used by `\left`, `\right` and the matrices
+fn delimiter(token: Option<Token>) -> Option<&'static str> {
+    match token? {
+        Token::Symbol('(') => Some("("),
+        Token::Symbol(')') => Some(")"),
+        Token::Symbol('[') => Some("["),
+        Token::Symbol(']') => Some("]"),
+        Token::Symbol('|') => Some("|"),
+        Token::Symbol('<') | Token::Command("langle") => Some("⟨"),
+        Token::Symbol('>') | Token::Command("rangle") => Some("⟩"),
+        Token::Command("{") | Token::Command("lbrace") => Some("{"),
+        Token::Command("}") | Token::Command("rbrace") => Some("}"),
+        Token::Command("|") => Some("‖"),
+        _ => None,
+    }
+}
+
+struct TexParser<'a> {
+    tokens: Vec<Token<'a>>,
+    position: usize,
+    /// the nesting of the group or the atom being parsed
+    depth: usize,
+    /// whether the nesting exceeded the `MAX_DEPTH`, the rest of the tokens are skipped
+    too_deep: bool,
+}
+
+impl<'a> TexParser<'a> {
+    fn new(tex: &'a str) -> Self {
+        Self {
+            tokens: tokenize(tex),
+            position: 0,
+            depth: 0,
+            too_deep: false,
+        }
+    }
+
+    /// the next token, skipping the spaces
+    fn peek(&mut self) -> Option<Token<'a>> {
+        while self.tokens.get(self.position) == Some(&Token::Space) {
+            self.position += 1;
+        }
+        self.tokens.get(self.position).copied()
+    }
+
+    fn next_token(&mut self) -> Option<Token<'a>> {
+        let token = self.peek();
+        self.position += 1;
+        token
+    }
+
+    /// parse one level deeper, `None` when the nesting exceeds the `MAX_DEPTH`
+    fn nested<T>(&mut self, parse: impl FnOnce(&mut Self) -> T) -> Option<T> {
+        if self.depth >= MAX_DEPTH {
+            self.too_deep = true;
+            self.position = self.tokens.len();
+            return None;
+        }
+        self.depth += 1;
+        let parsed = parse(self);
+        self.depth -= 1;
+        Some(parsed)
+    }
+
+    /// parse until the end of the group, the cell of a table or a `\right`
+    fn parse_row<MSG>(&mut self) -> Vec<Node<MSG>> {
+        let mut nodes = vec![];
+        while let Some(token) = self.peek() {
+            match token {
+                Token::Close | Token::Align | Token::NewRow => break,
+                Token::Command("right") | Token::Command("end") => break,
+                Token::Superscript | Token::Subscript => {
+                    let base = nodes.pop().unwrap_or_else(|| row(vec![]));
+                    nodes.push(self.parse_scripts(base));
+                }
+                _ => nodes.extend(self.parse_atom()),
+            }
+        }
+        nodes
+    }
+
+    /// parse the subscript and the superscript of the base
+    fn parse_scripts<MSG>(&mut self, base: Node<MSG>) -> Node<MSG> {
+        let mut subscript = None;
+        let mut superscript = None;
+        loop {
+            match self.peek() {
+                Some(Token::Subscript) if subscript.is_none() => {
+                    self.next_token();
+                    subscript = Some(self.parse_argument());
+                }
+                Some(Token::Superscript) if superscript.is_none() => {
+                    self.next_token();
+                    superscript = Some(self.parse_argument());
+                }
+                _ => break,
+            }
+        }
+        match (subscript, superscript) {
+            (Some(sub), Some(sup)) => element("msubsup", [], [base, sub, sup]),
+            (Some(sub), None) => element("msub", [], [base, sub]),
+            (None, Some(sup)) => element("msup", [], [base, sup]),
+            (None, None) => base,
+        }
+    }
+
+    /// parse a group in braces or a single atom
+    fn parse_argument<MSG>(&mut self) -> Node<MSG> {
+        match self.peek() {
+            Some(Token::Open) => {
+                self.next_token();
+                self.parse_group()
+            }
+            _ => self.parse_atom().unwrap_or_else(|| row(vec![])),
+        }
+    }
+
+    /// parse the content of a group until its closing brace, the opening brace is consumed
+    fn parse_group<MSG>(&mut self) -> Node<MSG> {
+        let nodes = self.nested(|parser| parser.parse_row()).unwrap_or_default();
+        if self.peek() == Some(Token::Close) {
+            self.next_token();
+        }
+        row(nodes)
+    }
+
+    /// the text of a group, such as the argument of `\text`
+    fn group_text(&mut self) -> String {
+        if self.peek() != Some(Token::Open) {
+            return String::new();
+        }
+        self.position += 1;
+        let mut content = String::new();
+        let mut depth = 0;
+        while let Some(token) = self.tokens.get(self.position).copied() {
+            self.position += 1;
+            match token {
+                Token::Open => depth += 1,
+                Token::Close if depth == 0 => break,
+                Token::Close => depth -= 1,
+                Token::Command(name) => content.push_str(name),
+                Token::Space => content.push(' '),
+                Token::Number(number) => content.push_str(number),
+                Token::Letter(c) | Token::Symbol(c) => content.push(c),
+                Token::Superscript => content.push('^'),
+                Token::Subscript => content.push('_'),
+                Token::Align => content.push('&'),
+                Token::NewRow => (),
+            }
+        }
+        content
+    }
+
+    fn parse_atom<MSG>(&mut self) -> Option<Node<MSG>> {
+        self.nested(|parser| parser.parse_token()).flatten()
+    }
+
+    fn parse_token<MSG>(&mut self) -> Option<Node<MSG>> {
+        let node = match self.next_token()? {
+            Token::Letter(c) => mi(c),
+            Token::Number(number) => element("mn", [], [text(number)]),
+            Token::Symbol('\'') => mo("′"),
+            Token::Symbol(c) => mo(c),
+            Token::Open => self.parse_group(),
+            Token::Command(name) => return self.parse_command(name),
+            // stray delimiters and separators outside of a group or a table
+            _ => return None,
+        };
+        Some(node)
+    }
+
+    fn parse_command<MSG>(&mut self, name: &'a str) -> Option<Node<MSG>> {
+        if let Some(identifier) = lookup(&IDENTIFIERS, name) {
+            return Some(mi(identifier));
+        }
+        if let Some(operator) = lookup(&OPERATORS, name) {
+            return Some(mo(operator));
+        }
+        if FUNCTIONS.contains(&name) {
+            return Some(element("mi", [attr("mathvariant", "normal")], [text(name)]));
+        }
+        if let Some(width) = lookup(&SPACES, name) {
+            return Some(element("mspace", [attr("width", width)], []));
+        }
+        if let Some(accent) = lookup(&ACCENTS, name) {
+            let base = self.parse_argument();
+            return Some(element(
+                "mover",
+                [attr("accent", "true")],
+                [base, mo(accent)],
+            ));
+        }
+        if let Some(variant) = lookup(&FONTS, name) {
+            let content = self.parse_argument();
+            return Some(element("mstyle", [attr("mathvariant", variant)], [content]));
+        }
+        let node = match name {
+            "frac" | "dfrac" | "tfrac" => {
+                let numerator = self.parse_argument();
+                let denominator = self.parse_argument();
+                element("mfrac", [], [numerator, denominator])
+            }
+            "sqrt" => {
+                if self.peek() == Some(Token::Symbol('[')) {
+                    self.next_token();
+                    let mut index = vec![];
+                    while !matches!(self.peek(), None | Some(Token::Symbol(']'))) {
+                        index.extend(self.parse_atom());
+                    }
+                    self.next_token();
+                    let base = self.parse_argument();
+                    element("mroot", [], [base, row(index)])
+                } else {
+                    element("msqrt", [], [self.parse_argument()])
+                }
+            }
+            "text" | "textrm" | "mbox" => element("mtext", [], [text(self.group_text())]),
+            "mathrm" | "operatorname" => element(
+                "mi",
+                [attr("mathvariant", "normal")],
+                [text(self.group_text())],
+            ),
+            "left" => self.parse_left(),
+            "begin" => self.parse_environment(),
+            "right" | "end" | "!" => return None,
+            " " => element("mtext", [], [text("\u{a0}")]),
+            "{" | "}" | "|" | "langle" | "rangle" | "lbrace" | "rbrace" => {
+                mo(delimiter(Some(Token::Command(name)))?)
+            }
+            "%" | "$" | "#" | "&" | "_" => mo(name),
+            _ => element(
+                "merror",
+                [],
+                [element("mtext", [], [text(format!("\\{}", name))])],
+            ),
+        };
+        Some(node)
+    }
+
+    /// parse `\left( ... \right)`, the `\left` is consumed
+    fn parse_left<MSG>(&mut self) -> Node<MSG> {
+        let mut nodes = vec![];
+        let open = self.next_token();
+        nodes.extend(delimiter(open).map(mo));
+        nodes.extend(self.parse_row());
+        if self.peek() == Some(Token::Command("right")) {
+            self.next_token();
+            let close = self.next_token();
+            nodes.extend(delimiter(close).map(mo));
+        }
+        element("mrow", [], nodes)
+    }
+
+    /// parse the matrices and the other tables, the `\begin` is consumed
+    fn parse_environment<MSG>(&mut self) -> Node<MSG> {
+        let name = self.group_text();
+        let mut rows = vec![];
+        loop {
+            let mut cells = vec![];
+            loop {
+                let cell = self.parse_row();
+                cells.push(element("mtd", [], [row(cell)]));
+                if self.peek() == Some(Token::Align) {
+                    self.next_token();
+                } else {
+                    break;
+                }
+            }
+            rows.push(element("mtr", [], cells));
+            match self.peek() {
+                Some(Token::NewRow) => {
+                    self.next_token();
+                }
+                Some(Token::Close) => {
+                    // a stray closing brace inside of the table
+                    self.next_token();
+                }
+                _ => break,
+            }
+        }
+        if self.peek() == Some(Token::Command("end")) {
+            self.next_token();
+            self.group_text();
+        }
+        let (open, close) = match name.as_str() {
+            "pmatrix" => ("(", ")"),
+            "bmatrix" => ("[", "]"),
+            "Bmatrix" => ("{", "}"),
+            "vmatrix" => ("|", "|"),
+            "Vmatrix" => ("‖", "‖"),
+            "cases" => ("{", ""),
+            _ => ("", ""),
+        };
+        let mut nodes = vec![];
+        if !open.is_empty() {
+            nodes.push(mo(open));
+        }
+        nodes.push(element("mtable", [], rows));
+        if !close.is_empty() {
+            nodes.push(mo(close));
+        }
+        row(nodes)
+    }
+}
+
+/// convert the TeX into a `<math>` element, the TeX is kept in an `annotation`.
+/// The TeX which is nested too deep is returned as text
+pub(crate) fn to_mathml<MSG>(tex: &str, display: bool) -> Node<MSG> {
+    let mut parser = TexParser::new(tex);
+    let mut nodes = vec![];
+    loop {
+        nodes.extend(parser.parse_row());
+        // skip the separators and the unmatched closing braces and continue
+        if parser.next_token().is_none() {
+            break;
+        }
+    }
+    if parser.too_deep {
+        return text(tex);
+    }
+    let display = if display { "block" } else { "inline" };
+    element(
+        "math",
+        [attr("xmlns", MATHML_NAMESPACE), attr("display", display)],
+        [element(
+            "semantics",
+            [],
+            [
+                element("mrow", [], nodes),
+                element(
+                    "annotation",
+                    [attr("encoding", "application/x-tex")],
+                    [text(tex)],
+                ),
+            ],
+        )],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// the rendered content of the `mrow` before the annotation
+    fn render(tex: &str) -> String {
+        let html = to_mathml::<()>(tex, false).render_to_string();
+        let start = html.find("<mrow>").expect("must have mrow") + "<mrow>".len();
+        let end = html
+            .find("</mrow><annotation")
+            .expect("must have annotation");
+        html[start..end].to_string()
+    }
+
+    #[test]
+    fn scripts() {
+        assert_eq!("<msup><mi>x</mi><mn>2</mn></msup>", render("x^2"));
+        assert_eq!(
+            "<msubsup><mi>a</mi><mi>i</mi><mrow><mi>n</mi><mo>+</mo><mn>1</mn></mrow></msubsup>",
+            render("a_i^{n+1}")
+        );
+    }
+
+    #[test]
+    fn fraction_and_root() {
+        assert_eq!(
+            "<mfrac><mi>π</mi><msqrt><mn>2</mn></msqrt></mfrac>",
+            render(r"\frac{\pi}{\sqrt{2}}")
+        );
+        assert_eq!("<mroot><mi>x</mi><mn>3</mn></mroot>", render(r"\sqrt[3]x"));
+    }
+
+    #[test]
+    fn matrix() {
+        assert_eq!(
+            "<mrow><mo>(</mo><mtable>\
+            <mtr><mtd><mn>1</mn></mtd><mtd><mn>0</mn></mtd></mtr>\
+            <mtr><mtd><mn>0</mn></mtd><mtd><mn>1</mn></mtd></mtr>\
+            </mtable><mo>)</mo></mrow>",
+            render(r"\begin{pmatrix} 1 & 0 \\ 0 & 1 \end{pmatrix}")
+        );
+    }
+
+    #[test]
+    fn unknown_command() {
+        assert_eq!("<merror><mtext>\\foo</mtext></merror>", render(r"\foo"));
+    }
+
+    #[test]
+    fn too_deep() {
+        for tex in &["{".repeat(1000), r"\hat".repeat(1000), "x^{".repeat(1000)] {
+            let html = to_mathml::<()>(tex, false).render_to_string();
+            assert_eq!(tex, &html);
+        }
+    }
+}
@@ -0,0 +1,321 @@
+//! Options on which markdown extensions are enabled and how the nodes are rendered
+use crate::markdown::Admonitions;
+#[cfg(feature = "highlight")]
+use crate::markdown::HighlightTheme;
+#[cfg(feature = "parse-html")]
+use crate::markdown::SanitizePolicy;
+#[cfg(feature = "svgbob")]
+use crate::markdown::SvgbobSettings;
+use crate::markdown::UrlPolicy;
+use pulldown_cmark::Options;
+
+/// How the alignment of the table columns is applied to the cells
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TableAlignment {
+    /// add a `text-left`, `text-center` or `text-right` class to the cells
+    Class,
+    /// add a `text-align` style to the cells
+    Style,
+}
+
+/// Options which controls the markdown extensions enabled in the parser
+/// and how this crate renders the resulting nodes.
+///
+/// The default enables all of the extensions, same as [`MarkdownOptions::new`].
+/// ```rust
+/// use sauron_markdown::MarkdownOptions;
+///
+/// // a restricted subset for user-generated comments
+/// let options = MarkdownOptions::commonmark()
+///     .strikethrough(true)
+///     .allow_html(false);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarkdownOptions {
+    pub(crate) tables: bool,
+    pub(crate) footnotes: bool,
+    pub(crate) strikethrough: bool,
+    pub(crate) tasklists: bool,
+    pub(crate) smart_punctuation: bool,
+    pub(crate) allow_html: bool,
+    pub(crate) soft_break_as_line_break: bool,
+    pub(crate) code_class_prefix: String,
+    pub(crate) table_alignment: TableAlignment,
+    #[cfg(feature = "parse-html")]
+    pub(crate) sanitize: Option<SanitizePolicy>,
+    pub(crate) url_policy: Option<UrlPolicy>,
+    pub(crate) heading_ids: bool,
+    pub(crate) heading_permalink: Option<String>,
+    pub(crate) toc_placeholder: bool,
+    pub(crate) title_fallback_h2: bool,
+    pub(crate) title_fallback_front_matter: bool,
+    pub(crate) front_matter: bool,
+    pub(crate) source_positions: bool,
+    pub(crate) block_keys: bool,
+    pub(crate) external_links_new_tab: bool,
+    pub(crate) math: bool,
+    #[cfg(feature = "mathml")]
+    pub(crate) mathml: bool,
+    pub(crate) admonitions: Option<Admonitions>,
+    #[cfg(feature = "highlight")]
+    pub(crate) highlight: bool,
+    #[cfg(feature = "highlight")]
+    pub(crate) highlight_theme: Option<HighlightTheme>,
+    pub(crate) strip_title: bool,
+    #[cfg(feature = "svgbob")]
+    pub(crate) svgbob: SvgbobSettings,
+}
+
+impl Default for MarkdownOptions {
+    fn default() -> Self {
+        Self {
+            tables: true,
+            footnotes: true,
+            strikethrough: true,
+            tasklists: true,
+            smart_punctuation: true,
+            allow_html: true,
+            soft_break_as_line_break: false,
+            code_class_prefix: "language-".to_string(),
+            table_alignment: TableAlignment::Class,
+            #[cfg(feature = "parse-html")]
+            sanitize: Some(SanitizePolicy::default()),
+            url_policy: Some(UrlPolicy::default()),
+            heading_ids: false,
+            heading_permalink: None,
+            toc_placeholder: false,
+            title_fallback_h2: false,
+            title_fallback_front_matter: false,
+            front_matter: true,
+            source_positions: false,
+            block_keys: false,
+            external_links_new_tab: false,
+            math: false,
+            #[cfg(feature = "mathml")]
+            mathml: true,
+            admonitions: None,
+            #[cfg(feature = "highlight")]
+            highlight: true,
+            #[cfg(feature = "highlight")]
+            highlight_theme: None,
+            strip_title: false,
+            #[cfg(feature = "svgbob")]
+            svgbob: SvgbobSettings::default(),
+        }
+    }
+}
+
+impl MarkdownOptions {
+    /// create options with all of the extensions enabled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// create options with none of the extensions enabled, plain commonmark only
+    pub fn commonmark() -> Self {
+        Self {
+            tables: false,
+            footnotes: false,
+            strikethrough: false,
+            tasklists: false,
+            smart_punctuation: false,
+            ..Self::default()
+        }
+    }
+
+    /// enable or disable tables
+    pub fn tables(mut self, enable: bool) -> Self {
+        self.tables = enable;
+        self
+    }
+
+    /// enable or disable footnotes
+    pub fn footnotes(mut self, enable: bool) -> Self {
+        self.footnotes = enable;
+        self
+    }
+
+    /// enable or disable strikethrough
+    pub fn strikethrough(mut self, enable: bool) -> Self {
+        self.strikethrough = enable;
+        self
+    }
+
+    /// enable or disable task lists
+    pub fn tasklists(mut self, enable: bool) -> Self {
+        self.tasklists = enable;
+        self
+    }
+
+    /// enable or disable smart punctuation
+    pub fn smart_punctuation(mut self, enable: bool) -> Self {
+        self.smart_punctuation = enable;
+        self
+    }
+
+    /// when disabled, the html in the markdown is displayed as text
+    pub fn allow_html(mut self, allow: bool) -> Self {
+        self.allow_html = allow;
+        self
+    }
+
+    /// when enabled, soft breaks are rendered as `<br>` instead of a newline
+    pub fn soft_break_as_line_break(mut self, enable: bool) -> Self {
+        self.soft_break_as_line_break = enable;
+        self
+    }
+
+    /// the prefix prepended to the language of fenced code blocks
+    /// when used as the class of the `code` element, defaults to `language-`
+    pub fn code_class_prefix(mut self, prefix: impl ToString) -> Self {
+        self.code_class_prefix = prefix.to_string();
+        self
+    }
+
+    /// how the column alignment is applied to the table cells,
+    /// defaults to [`TableAlignment::Class`]
+    pub fn table_alignment(mut self, table_alignment: TableAlignment) -> Self {
+        self.table_alignment = table_alignment;
+        self
+    }
+
+    /// the policy used for sanitizing the html in the markdown,
+    /// defaults to [`SanitizePolicy::default`], `None` keeps all of the html as is
+    #[cfg(feature = "parse-html")]
+    pub fn sanitize_policy(mut self, policy: Option<SanitizePolicy>) -> Self {
+        self.sanitize = policy;
+        self
+    }
+
+    /// the policy applied to the urls of the markdown links and images,
+    /// defaults to [`UrlPolicy::default`], `None` keeps all of the urls as is
+    pub fn url_policy(mut self, policy: Option<UrlPolicy>) -> Self {
+        self.url_policy = policy;
+        self
+    }
+
+    /// when enabled, the headings are given a unique id derived from their text,
+    /// or the id specified with `{#custom-id}` at the end of the heading
+    pub fn heading_ids(mut self, enable: bool) -> Self {
+        self.heading_ids = enable;
+        self
+    }
+
+    /// add an `<a class="anchor">` link to the heading itself, displaying this text,
+    /// only applies when `heading_ids` is enabled
+    pub fn heading_permalink(mut self, text: Option<impl ToString>) -> Self {
+        self.heading_permalink = text.map(|t| t.to_string());
+        self
+    }
+
+    /// replace a paragraph containing only `[TOC]` with the table of contents,
+    /// the headings are given ids when enabled
+    pub fn toc_placeholder(mut self, enable: bool) -> Self {
+        self.toc_placeholder = enable;
+        self
+    }
+
+    /// use the first h2 as the title when there is no h1, in `parse_with_title`
+    pub fn title_fallback_h2(mut self, enable: bool) -> Self {
+        self.title_fallback_h2 = enable;
+        self
+    }
+
+    /// use the `title` of the front matter as the title when there is no h1,
+    /// in `parse_with_title`. It is checked before the fallback to the first h2
+    pub fn title_fallback_front_matter(mut self, enable: bool) -> Self {
+        self.title_fallback_front_matter = enable;
+        self
+    }
+
+    /// remove the heading used as the title from the nodes, in `parse_with_title`
+    pub fn strip_title(mut self, enable: bool) -> Self {
+        self.strip_title = enable;
+        self
+    }
+
+    /// detect the `---` YAML or `+++` TOML front matter at the start of the markdown
+    /// and remove it before converting the markdown, enabled by default
+    pub fn front_matter(mut self, enable: bool) -> Self {
+        self.front_matter = enable;
+        self
+    }
+
+    /// add a `data-sourcepos` attribute to the block level elements,
+    /// with the position of the block in the markdown source, ie: `3:1-4:12`.
+    /// The lines and columns starts at 1, the columns are counted in bytes.
+    pub fn source_positions(mut self, enable: bool) -> Self {
+        self.source_positions = enable;
+        self
+    }
+
+    /// add a `key` attribute to the top level nodes, derived from the hash of their source
+    /// and the occurrence index of the same content. The keys lets sauron match the unchanged
+    /// blocks when diffing, instead of patching every block after an inserted one.
+    pub fn block_keys(mut self, enable: bool) -> Self {
+        self.block_keys = enable;
+        self
+    }
+
+    /// open the links to other sites in a new tab, with `target="_blank" rel="noopener"`
+    pub fn external_links_new_tab(mut self, enable: bool) -> Self {
+        self.external_links_new_tab = enable;
+        self
+    }
+
+    /// recognize the inline `$...$` and display `$$...$$` math and the ```` ```math ````
+    /// code blocks, which are rendered into `<span class="math inline">` and
+    /// `<div class="math display">` containing the TeX source.
+    /// A processor registered to `math` is used for rendering the math instead.
+    pub fn math(mut self, enable: bool) -> Self {
+        self.math = enable;
+        self
+    }
+
+    /// render the math into MathML instead of the TeX source, enabled by default
+    #[cfg(feature = "mathml")]
+    pub fn mathml(mut self, enable: bool) -> Self {
+        self.mathml = enable;
+        self
+    }
+
+    /// render the `> [!NOTE]` blockquotes and the `!!! note` blocks of these kinds
+    /// into admonitions, disabled by default
+    pub fn admonitions(mut self, admonitions: Option<Admonitions>) -> Self {
+        self.admonitions = admonitions;
+        self
+    }
+
+    /// highlight the code of the supported languages with `<span class="tok-keyword">` and
+    /// the other `tok-*` classes, enabled by default
+    #[cfg(feature = "highlight")]
+    pub fn highlight(mut self, enable: bool) -> Self {
+        self.highlight = enable;
+        self
+    }
+
+    /// use the inline styles of the theme for the highlighted tokens instead of the classes
+    #[cfg(feature = "highlight")]
+    pub fn highlight_theme(mut self, theme: Option<HighlightTheme>) -> Self {
+        self.highlight_theme = theme;
+        self
+    }
+
+    /// the settings used for rendering `bob` code blocks into svg
+    #[cfg(feature = "svgbob")]
+    pub fn svgbob_settings(mut self, settings: SvgbobSettings) -> Self {
+        self.svgbob = settings;
+        self
+    }
+
+    /// the pulldown-cmark options derived from the enabled extensions
+    pub(crate) fn pulldown_options(&self) -> Options {
+        let mut options = Options::empty();
+        options.set(Options::ENABLE_TABLES, self.tables);
+        options.set(Options::ENABLE_FOOTNOTES, self.footnotes);
+        options.set(Options::ENABLE_STRIKETHROUGH, self.strikethrough);
+        options.set(Options::ENABLE_TASKLISTS, self.tasklists);
+        options.set(Options::ENABLE_SMART_PUNCTUATION, self.smart_punctuation);
+        options
+    }
+}
@@ -0,0 +1,72 @@
+//! Plugins which can alter how the markdown is converted into nodes
+use crate::markdown::CodeFenceInfo;
+use sauron::Node;
+
+/// Processes the content of fenced code blocks of the languages it is registered to.
+///
+/// Closures with the signature `Fn(&CodeFenceInfo, &str) -> Option<Node<MSG>>`
+/// can be used as a processor.
+pub trait CodeFenceProcessor<MSG> {
+    /// process the code of the fenced code block,
+    /// returning `None` will fallback to the default `<pre><code>` block
+    fn process(&self, info: &CodeFenceInfo, code: &str) -> Option<Node<MSG>>;
+}
+
+impl<MSG, F> CodeFenceProcessor<MSG> for F
+where
+    F: Fn(&CodeFenceInfo, &str) -> Option<Node<MSG>>,
+{
+    fn process(&self, info: &CodeFenceInfo, code: &str) -> Option<Node<MSG>> {
+        self(info, code)
+    }
+}
+
+/// Transforms the nodes after the whole markdown has been assembled.
+///
+/// Each element is visited after its children have been transformed.
+/// Returning `None` drops the node, otherwise the returned node replaces it,
+/// which can be the same node, a modified one or a wrapper containing it.
+///
+/// Closures with the signature `Fn(Node<MSG>) -> Option<Node<MSG>>`
+/// can be used as a transform.
+pub trait NodeTransform<MSG> {
+    /// transform the node
+    fn transform(&self, node: Node<MSG>) -> Option<Node<MSG>>;
+}
+
+impl<MSG, F> NodeTransform<MSG> for F
+where
+    F: Fn(Node<MSG>) -> Option<Node<MSG>>,
+{
+    fn transform(&self, node: Node<MSG>) -> Option<Node<MSG>> {
+        self(node)
+    }
+}
+
+/// apply the transforms in order to each of the nodes and their descendants
+pub(crate) fn transform_nodes<MSG>(
+    nodes: Vec<Node<MSG>>,
+    transforms: &[Box<dyn NodeTransform<MSG>>],
+) -> Vec<Node<MSG>> {
+    nodes
+        .into_iter()
+        .filter_map(|node| transform_node(node, transforms))
+        .collect()
+}
+
+pub(crate) fn transform_node<MSG>(
+    node: Node<MSG>,
+    transforms: &[Box<dyn NodeTransform<MSG>>],
+) -> Option<Node<MSG>> {
+    let node = match node {
+        Node::Element(mut element) => {
+            let children = std::mem::take(&mut element.children);
+            element.children = transform_nodes(children, transforms);
+            Node::Element(element)
+        }
+        node => node,
+    };
+    transforms
+        .iter()
+        .try_fold(node, |node, transform| transform.transform(node))
+}
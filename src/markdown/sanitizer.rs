@@ -0,0 +1,274 @@
+//! Sanitizing of the html in the markdown using an allowlist of tags and attributes
+use crate::markdown::url_policy::url_scheme;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// the tags which are removed together with their content
+const CLEAN_CONTENT_TAGS: [&str; 6] = ["script", "style", "iframe", "object", "embed", "template"];
+
+const DEFAULT_TAGS: [&str; 72] = [
+    "a",
+    "abbr",
+    "acronym",
+    "area",
+    "article",
+    "aside",
+    "b",
+    "bdi",
+    "bdo",
+    "blockquote",
+    "br",
+    "caption",
+    "center",
+    "cite",
+    "code",
+    "col",
+    "colgroup",
+    "data",
+    "dd",
+    "del",
+    "details",
+    "dfn",
+    "div",
+    "dl",
+    "dt",
+    "em",
+    "figcaption",
+    "figure",
+    "footer",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hgroup",
+    "hr",
+    "i",
+    "img",
+    "ins",
+    "kbd",
+    "li",
+    "map",
+    "mark",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "q",
+    "rp",
+    "rt",
+    "ruby",
+    "s",
+    "samp",
+    "small",
+    "span",
+    "strike",
+    "strong",
+    "sub",
+    "summary",
+    "sup",
+    "table",
+    "tbody",
+    "td",
+    "th",
+    "thead",
+    "time",
+    "tr",
+    "u",
+    "ul",
+    "var",
+];
+
+const DEFAULT_GENERIC_ATTRIBUTES: [&str; 4] = ["class", "dir", "lang", "title"];
+
+const DEFAULT_TAG_ATTRIBUTES: [(&str, &[&str]); 12] = [
+    ("a", &["href", "hreflang"]),
+    ("bdo", &["dir"]),
+    ("blockquote", &["cite"]),
+    ("col", &["align", "char", "charoff", "span"]),
+    ("del", &["cite", "datetime"]),
+    ("details", &["open"]),
+    ("img", &["align", "alt", "height", "src", "width"]),
+    ("ins", &["cite", "datetime"]),
+    ("ol", &["start"]),
+    ("q", &["cite"]),
+    ("td", &["align", "colspan", "rowspan"]),
+    ("th", &["align", "colspan", "rowspan"]),
+];
+
+/// the attributes which contains urls
+const URL_ATTRIBUTES: [&str; 5] = ["href", "src", "cite", "action", "longdesc"];
+
+const DEFAULT_URL_SCHEMES: [&str; 3] = ["http", "https", "mailto"];
+
+/// An allowlist of the html tags, attributes and url schemes
+/// which are kept when converting the html in the markdown.
+///
+/// Tags which are not allowed are removed but their content is kept,
+/// except for tags such as `script` and `style` which are removed together with their content.
+/// The default policy is safe for rendering untrusted markdown.
+/// ```rust
+/// use sauron_markdown::SanitizePolicy;
+///
+/// let policy = SanitizePolicy::new()
+///     .allow_tags(["video"])
+///     .allow_attributes("video", ["src", "controls"])
+///     .link_rel(None::<String>);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SanitizePolicy {
+    tags: BTreeSet<String>,
+    generic_attributes: BTreeSet<String>,
+    tag_attributes: BTreeMap<String, BTreeSet<String>>,
+    url_schemes: BTreeSet<String>,
+    link_rel: Option<String>,
+}
+
+impl Default for SanitizePolicy {
+    fn default() -> Self {
+        Self {
+            tags: to_set(DEFAULT_TAGS.iter()),
+            generic_attributes: to_set(DEFAULT_GENERIC_ATTRIBUTES.iter()),
+            tag_attributes: DEFAULT_TAG_ATTRIBUTES
+                .iter()
+                .map(|(tag, attributes)| (tag.to_string(), to_set(attributes.iter())))
+                .collect(),
+            url_schemes: to_set(DEFAULT_URL_SCHEMES.iter()),
+            link_rel: Some("noopener noreferrer".to_string()),
+        }
+    }
+}
+
+impl SanitizePolicy {
+    /// create the default policy
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// create a policy which allows nothing, only the text content is kept
+    pub fn empty() -> Self {
+        Self {
+            tags: BTreeSet::new(),
+            generic_attributes: BTreeSet::new(),
+            tag_attributes: BTreeMap::new(),
+            url_schemes: BTreeSet::new(),
+            link_rel: None,
+        }
+    }
+
+    /// allow these tags
+    pub fn allow_tags(mut self, tags: impl IntoIterator<Item = impl ToString>) -> Self {
+        self.tags.extend(tags.into_iter().map(|t| t.to_string()));
+        self
+    }
+
+    /// disallow these tags
+    pub fn remove_tags(mut self, tags: impl IntoIterator<Item = impl ToString>) -> Self {
+        for tag in tags {
+            self.tags.remove(&tag.to_string());
+        }
+        self
+    }
+
+    /// allow these attributes on all of the allowed tags
+    pub fn allow_generic_attributes(
+        mut self,
+        attributes: impl IntoIterator<Item = impl ToString>,
+    ) -> Self {
+        self.generic_attributes
+            .extend(attributes.into_iter().map(|a| a.to_string()));
+        self
+    }
+
+    /// allow these attributes on this tag
+    pub fn allow_attributes(
+        mut self,
+        tag: impl ToString,
+        attributes: impl IntoIterator<Item = impl ToString>,
+    ) -> Self {
+        self.tag_attributes
+            .entry(tag.to_string())
+            .or_default()
+            .extend(attributes.into_iter().map(|a| a.to_string()));
+        self
+    }
+
+    /// allow urls with these schemes, relative urls are always allowed
+    pub fn allow_url_schemes(mut self, schemes: impl IntoIterator<Item = impl ToString>) -> Self {
+        self.url_schemes
+            .extend(schemes.into_iter().map(|s| s.to_string().to_lowercase()));
+        self
+    }
+
+    /// the `rel` attribute set on every `a` element,
+    /// defaults to `noopener noreferrer`, `None` keeps the `rel` of the html if allowed
+    pub fn link_rel(mut self, rel: Option<impl ToString>) -> Self {
+        self.link_rel = rel.map(|r| r.to_string());
+        self
+    }
+
+    /// whether the tag is kept
+    pub(crate) fn is_tag_allowed(&self, tag: &str) -> bool {
+        self.tags.contains(tag)
+    }
+
+    /// whether the tag is removed together with its content
+    pub(crate) fn is_clean_content_tag(&self, tag: &str) -> bool {
+        !self.is_tag_allowed(tag) && CLEAN_CONTENT_TAGS.contains(&tag)
+    }
+
+    /// whether the attribute with this value is kept on the tag
+    pub(crate) fn is_attribute_allowed(&self, tag: &str, attribute: &str, value: &str) -> bool {
+        if attribute == "rel" && tag == "a" && self.link_rel.is_some() {
+            return false;
+        }
+        let allowed = self.generic_attributes.contains(attribute)
+            || self
+                .tag_attributes
+                .get(tag)
+                .map(|attributes| attributes.contains(attribute))
+                .unwrap_or(false);
+        if allowed && URL_ATTRIBUTES.contains(&attribute) {
+            self.is_url_allowed(value)
+        } else {
+            allowed
+        }
+    }
+
+    /// whether the url is relative or has an allowed scheme
+    pub(crate) fn is_url_allowed(&self, url: &str) -> bool {
+        match url_scheme(url) {
+            Some(scheme) => self.url_schemes.contains(&scheme),
+            None => true,
+        }
+    }
+
+    /// the `rel` attribute to be added to the tag
+    pub(crate) fn link_rel_for(&self, tag: &str) -> Option<&str> {
+        if tag == "a" {
+            self.link_rel.as_deref()
+        } else {
+            None
+        }
+    }
+}
+
+fn to_set<'a>(items: impl IntoIterator<Item = &'a &'a str>) -> BTreeSet<String> {
+    items.into_iter().map(|item| item.to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn obfuscated_javascript_url() {
+        let policy = SanitizePolicy::default();
+        assert!(!policy.is_url_allowed("javascript:alert(1)"));
+        assert!(!policy.is_url_allowed(" JaVa\tScRiPt:alert(1)"));
+        assert!(!policy.is_url_allowed("&#106;avascript:alert(1)"));
+        assert!(policy.is_url_allowed("https://example.com"));
+        assert!(policy.is_url_allowed("../relative/path:with-colon"));
+    }
+}
@@ -0,0 +1,118 @@
+//! Mapping the byte offsets of the markdown source into lines and columns
+use std::ops::Range;
+
+/// the byte offsets where each line of the source starts
+pub(crate) struct LineIndex {
+    line_starts: Vec<usize>,
+}
+
+impl LineIndex {
+    pub(crate) fn new(src: &str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(src.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { line_starts }
+    }
+
+    /// the 1-based line and column of the byte offset
+    fn position(&self, offset: usize) -> (usize, usize) {
+        let line = match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(line) => line - 1,
+        };
+        (line + 1, offset - self.line_starts[line] + 1)
+    }
+
+    /// the position of the range in the format used by cmark-gfm, ie: `1:1-2:5`,
+    /// the end position is the last byte of the range
+    pub(crate) fn sourcepos(&self, range: &Range<usize>) -> String {
+        let (start_line, start_column) = self.position(range.start);
+        let (end_line, end_column) = self.position(range.end.saturating_sub(1).max(range.start));
+        format!(
+            "{}:{}-{}:{}",
+            start_line, start_column, end_line, end_column
+        )
+    }
+}
+
+/// Maps the byte offsets of a markdown text into the offsets in the source.
+///
+/// The markdown body is a slice of the source, while the content of the `!!!` admonitions
+/// is dedented, so each line of the content starts at a different offset in the source.
+pub(crate) struct LineOffsets {
+    /// the offset where each line starts in the text and in the source
+    lines: Vec<(usize, usize)>,
+}
+
+impl LineOffsets {
+    /// the text is a slice of the source which starts at `start`
+    pub(crate) fn new(start: usize) -> Self {
+        Self {
+            lines: vec![(0, start)],
+        }
+    }
+
+    /// create the offsets of a text whose lines are added with `push_line`
+    pub(crate) fn empty() -> Self {
+        Self { lines: vec![] }
+    }
+
+    /// the line of the text which starts at `text_start` is at `source_start` of the source
+    pub(crate) fn push_line(&mut self, text_start: usize, source_start: usize) {
+        self.lines.push((text_start, source_start));
+    }
+
+    /// the offset in the source of the offset in the text
+    pub(crate) fn source_offset(&self, offset: usize) -> usize {
+        let line = self
+            .lines
+            .partition_point(|(text_start, _)| *text_start <= offset)
+            .saturating_sub(1);
+        match self.lines.get(line) {
+            Some((text_start, source_start)) => source_start + offset.saturating_sub(*text_start),
+            None => offset,
+        }
+    }
+
+    /// the range in the source of the range in the text,
+    /// the end is mapped from the last byte so it does not extend into the next line
+    pub(crate) fn source_range(&self, range: Range<usize>) -> Range<usize> {
+        let start = self.source_offset(range.start);
+        if range.end > range.start {
+            start..self.source_offset(range.end - 1) + 1
+        } else {
+            start..start
+        }
+    }
+}
+
+/// exclude the trailing line endings from the range
+pub(crate) fn trim_range(src: &str, range: Range<usize>) -> Range<usize> {
+    let trimmed = src[range.clone()].trim_end_matches(|c| c == '\n' || c == '\r');
+    range.start..range.start + trimmed.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sourcepos() {
+        let src = "# Title\n\nline one\nline two\n";
+        let lines = LineIndex::new(src);
+        assert_eq!(lines.sourcepos(&trim_range(src, 0..8)), "1:1-1:7");
+        assert_eq!(lines.sourcepos(&trim_range(src, 9..27)), "3:1-4:8");
+    }
+
+    #[test]
+    fn line_offsets() {
+        assert_eq!(LineOffsets::new(4).source_range(2..6), 6..10);
+        // the text `a\nb\n` dedented from `    a\n    b\n` at 10 of the source
+        let mut offsets = LineOffsets::empty();
+        offsets.push_line(0, 14);
+        offsets.push_line(2, 20);
+        assert_eq!(offsets.source_range(0..2), 14..16);
+        assert_eq!(offsets.source_range(2..3), 20..21);
+        assert_eq!(offsets.source_range(0..4), 14..22);
+    }
+}
@@ -0,0 +1,61 @@
+//! Renders `bob` fenced code blocks into svg using svgbob
+use crate::markdown::{CodeFenceInfo, CodeFenceProcessor};
+use sauron::*;
+
+/// The settings used when rendering the svgbob diagrams
+#[derive(Debug, Clone, PartialEq)]
+pub struct SvgbobSettings {
+    /// the font family of the text in the diagram
+    pub font_family: String,
+    /// the font size of the text in the diagram
+    pub font_size: usize,
+    /// the width of the lines in the diagram
+    pub stroke_width: f32,
+    /// the background color of the diagram
+    pub background: String,
+}
+
+impl Default for SvgbobSettings {
+    fn default() -> Self {
+        let settings = svgbob::Settings::default();
+        Self {
+            font_family: settings.font_family,
+            font_size: settings.font_size,
+            stroke_width: settings.stroke_width,
+            background: settings.background,
+        }
+    }
+}
+
+impl From<&SvgbobSettings> for svgbob::Settings {
+    fn from(settings: &SvgbobSettings) -> Self {
+        svgbob::Settings {
+            font_family: settings.font_family.clone(),
+            font_size: settings.font_size,
+            stroke_width: settings.stroke_width,
+            background: settings.background.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+/// A code fence processor which converts the ascii diagram into svg
+pub(crate) struct SvgbobProcessor {
+    settings: svgbob::Settings,
+}
+
+impl SvgbobProcessor {
+    pub(crate) fn new(settings: &SvgbobSettings) -> Self {
+        Self {
+            settings: settings.into(),
+        }
+    }
+}
+
+impl<MSG> CodeFenceProcessor<MSG> for SvgbobProcessor {
+    fn process(&self, info: &CodeFenceInfo, content: &str) -> Option<Node<MSG>> {
+        let svg = svgbob::to_svg_with_settings(content, &self.settings);
+        let lang = info.lang.as_deref().unwrap_or("bob");
+        Some(pre([], [code([class(lang)], [raw_html(&svg)])]))
+    }
+}
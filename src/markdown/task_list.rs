@@ -0,0 +1,103 @@
+//! Task list checkboxes which can be toggled
+use std::ops::Range;
+
+/// The task list item whose checkbox was toggled
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskToggle {
+    /// the index of the task in the document, starting at 0
+    pub index: usize,
+    /// the byte range of the `[ ]` or `[x]` marker in the markdown source
+    pub range: Range<usize>,
+    /// whether the task is checked after the toggle
+    pub checked: bool,
+}
+
+impl TaskToggle {
+    /// return the markdown source with the marker of this task changed into `[x]` or `[ ]`,
+    /// or `None` if the marker is not found in the source at the range of the task
+    /// ```rust
+    /// use sauron_markdown::TaskToggle;
+    ///
+    /// let src = "- [ ] write docs\n- [x] write tests";
+    /// let toggle = TaskToggle {
+    ///     index: 0,
+    ///     range: 2..5,
+    ///     checked: true,
+    /// };
+    /// assert_eq!(
+    ///     toggle.apply(src).as_deref(),
+    ///     Some("- [x] write docs\n- [x] write tests")
+    /// );
+    /// ```
+    pub fn apply(&self, src: &str) -> Option<String> {
+        let marker_start = self.range.start + src.get(self.range.clone())?.find('[')?;
+        let marker = src.get(marker_start..marker_start + 3)?;
+        if !matches!(marker, "[ ]" | "[x]" | "[X]") {
+            return None;
+        }
+        let replacement = if self.checked { "[x]" } else { "[ ]" };
+        let mut src = src.to_string();
+        src.replace_range(marker_start..marker_start + 3, replacement);
+        Some(src)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown::tests::{count_listeners, recorder};
+    use crate::markdown::{Admonitions, MarkdownOptions, MarkdownParser};
+
+    /// the toggles which the parser creates for the checkboxes of the markdown
+    fn parsed_toggles(md: &str, options: MarkdownOptions) -> Vec<TaskToggle> {
+        let (toggles, record) = recorder();
+        let parser = MarkdownParser::with_options(options).on_task_toggle(record);
+        let nodes = parser.parse(md);
+        let toggles = toggles.take();
+        assert_eq!(count_listeners(&nodes, "input", "change"), toggles.len());
+        toggles
+    }
+
+    #[test]
+    fn toggles_of_the_parsed_checkboxes() {
+        // the ranges are offset by the front matter
+        let md = "---\ntitle: Todo\n---\n# Todo\n\n- [ ] write docs\n- [x] write tests\n";
+        let toggles = parsed_toggles(md, MarkdownOptions::default());
+        assert_eq!(toggles.len(), 2);
+        assert_eq!((toggles[0].index, toggles[0].checked), (0, true));
+        assert_eq!(md[toggles[0].range.clone()].trim(), "[ ]");
+        assert_eq!((toggles[1].index, toggles[1].checked), (1, false));
+        assert_eq!(md[toggles[1].range.clone()].trim(), "[x]");
+
+        let md = toggles[0].apply(md).expect("must toggle");
+        let md = toggles[1].apply(&md).expect("must toggle");
+        assert_eq!(
+            md,
+            "---\ntitle: Todo\n---\n# Todo\n\n- [x] write docs\n- [ ] write tests\n"
+        );
+    }
+
+    #[test]
+    fn toggles_in_admonition() {
+        let md = "!!! note\n    - [ ] first\n    - [ ] second\n";
+        let options = MarkdownOptions::default().admonitions(Some(Admonitions::new()));
+        let toggles = parsed_toggles(md, options);
+        assert_eq!(toggles.len(), 2);
+        assert_eq!(toggles[1].index, 1);
+        assert_eq!(
+            toggles[1].apply(md).as_deref(),
+            Some("!!! note\n    - [ ] first\n    - [x] second\n")
+        );
+    }
+
+    #[test]
+    fn stale_range() {
+        let toggle = TaskToggle {
+            index: 0,
+            range: 0..3,
+            checked: false,
+        };
+        assert_eq!(toggle.apply("not a task"), None);
+        assert_eq!(toggle.apply("- "), None);
+    }
+}
@@ -0,0 +1,93 @@
+//! Table of contents of the headings in the markdown
+use sauron::*;
+
+/// A heading in the table of contents, with the headings nested under it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Toc {
+    /// the level of the heading, 1 for `h1` up to 6 for `h6`
+    pub level: u32,
+    /// the text content of the heading
+    pub text: String,
+    /// the id of the heading, used for linking to it
+    pub id: String,
+    /// the headings with a higher level which follows this heading
+    pub children: Vec<Toc>,
+}
+
+impl Toc {
+    pub(crate) fn new(level: u32, text: String, id: String) -> Self {
+        Self {
+            level,
+            text,
+            id,
+            children: vec![],
+        }
+    }
+}
+
+/// nest the headings in the order they appear in the document
+pub(crate) fn nest(headings: Vec<Toc>) -> Vec<Toc> {
+    let mut toc = vec![];
+    for heading in headings {
+        insert(&mut toc, heading);
+    }
+    toc
+}
+
+fn insert(siblings: &mut Vec<Toc>, heading: Toc) {
+    match siblings.last_mut() {
+        Some(last) if last.level < heading.level => insert(&mut last.children, heading),
+        _ => siblings.push(heading),
+    }
+}
+
+/// render the table of contents as a `nav` with nested lists of links to the headings
+/// ```rust
+/// use sauron_markdown::{parse_with_toc, render_toc};
+/// use sauron_markdown::sauron::*;
+///
+/// let (toc, _nodes) = parse_with_toc::<()>("# Intro\n## Usage");
+/// let view: Node<()> = render_toc(&toc);
+/// assert_eq!(
+///     view.render_to_string(),
+///     "<nav class=\"toc\"><ul><li><a href=\"#intro\">Intro</a>\
+///     <ul><li><a href=\"#usage\">Usage</a></li></ul></li></ul></nav>"
+/// );
+/// ```
+pub fn render_toc<MSG>(toc: &[Toc]) -> Node<MSG> {
+    nav([class("toc")], [toc_list(toc)])
+}
+
+fn toc_list<MSG>(toc: &[Toc]) -> Node<MSG> {
+    ul(
+        [],
+        toc.iter().map(|entry| {
+            let mut children = vec![a([href(format!("#{}", entry.id))], [text(&entry.text)])];
+            if !entry.children.is_empty() {
+                children.push(toc_list(&entry.children));
+            }
+            li([], children)
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nest_headings() {
+        let headings = vec![
+            Toc::new(2, "a".into(), "a".into()),
+            Toc::new(3, "b".into(), "b".into()),
+            Toc::new(4, "c".into(), "c".into()),
+            Toc::new(3, "d".into(), "d".into()),
+            Toc::new(1, "e".into(), "e".into()),
+        ];
+        let toc = nest(headings);
+        assert_eq!(toc.len(), 2);
+        assert_eq!(toc[0].children.len(), 2);
+        assert_eq!(toc[0].children[0].children[0].text, "c");
+        assert_eq!(toc[1].text, "e");
+    }
+}
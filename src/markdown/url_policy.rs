@@ -0,0 +1,159 @@
+//! Filtering of the urls of the markdown links and images
+use std::collections::BTreeSet;
+
+const DEFAULT_URL_SCHEMES: [&str; 3] = ["http", "https", "mailto"];
+
+/// svg is excluded since it can contain scripts
+const DEFAULT_IMAGE_MIME_TYPES: [&str; 5] = [
+    "image/png",
+    "image/jpeg",
+    "image/gif",
+    "image/webp",
+    "image/bmp",
+];
+
+/// What is done to the urls which are not allowed by the [`UrlPolicy`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum DisallowedUrl {
+    /// the `href` or `src` attribute is removed, the content of the link is kept
+    Remove,
+    /// the url is replaced with this url, ie: `#`
+    Replace(String),
+}
+
+/// The policy applied to the urls of the markdown links and images.
+///
+/// The default allows relative urls, `http`, `https` and `mailto` urls,
+/// and `data:` urls only on images with a png, jpeg, gif, webp or bmp mime type.
+/// ```rust
+/// use sauron_markdown::{DisallowedUrl, UrlPolicy};
+///
+/// let policy = UrlPolicy::new()
+///     .allow_schemes(["ftp"])
+///     .allow_relative(false)
+///     .disallowed(DisallowedUrl::Replace("#".to_string()));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct UrlPolicy {
+    schemes: BTreeSet<String>,
+    allow_relative: bool,
+    image_mime_types: BTreeSet<String>,
+    disallowed: DisallowedUrl,
+}
+
+/// Where the url is used, data urls are only allowed on images
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum UrlKind {
+    Link,
+    Image,
+}
+
+impl Default for UrlPolicy {
+    fn default() -> Self {
+        Self {
+            schemes: DEFAULT_URL_SCHEMES.iter().map(|s| s.to_string()).collect(),
+            allow_relative: true,
+            image_mime_types: DEFAULT_IMAGE_MIME_TYPES
+                .iter()
+                .map(|m| m.to_string())
+                .collect(),
+            disallowed: DisallowedUrl::Remove,
+        }
+    }
+}
+
+impl UrlPolicy {
+    /// create the default url policy
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// allow urls with these schemes
+    pub fn allow_schemes(mut self, schemes: impl IntoIterator<Item = impl ToString>) -> Self {
+        self.schemes
+            .extend(schemes.into_iter().map(|s| s.to_string().to_lowercase()));
+        self
+    }
+
+    /// disallow urls with these schemes
+    pub fn remove_schemes(mut self, schemes: impl IntoIterator<Item = impl ToString>) -> Self {
+        for scheme in schemes {
+            self.schemes.remove(&scheme.to_string().to_lowercase());
+        }
+        self
+    }
+
+    /// whether relative urls such as `../docs/` and `#section` are allowed
+    pub fn allow_relative(mut self, allow: bool) -> Self {
+        self.allow_relative = allow;
+        self
+    }
+
+    /// the mime types allowed in `data:` urls of images,
+    /// replacing the default mime types
+    pub fn image_mime_types(mut self, mime_types: impl IntoIterator<Item = impl ToString>) -> Self {
+        self.image_mime_types = mime_types
+            .into_iter()
+            .map(|m| m.to_string().to_lowercase())
+            .collect();
+        self
+    }
+
+    /// what is done to the urls which are not allowed
+    pub fn disallowed(mut self, disallowed: DisallowedUrl) -> Self {
+        self.disallowed = disallowed;
+        self
+    }
+
+    /// whether the url is allowed to be used in a link or image
+    pub(crate) fn is_allowed(&self, url: &str, kind: UrlKind) -> bool {
+        match url_scheme(url).as_deref() {
+            Some("data") => kind == UrlKind::Image && self.is_image_data_allowed(url),
+            Some(scheme) => self.schemes.contains(scheme),
+            None => self.allow_relative,
+        }
+    }
+
+    /// the url to be used in place of the url, `None` if the attribute is to be removed
+    pub(crate) fn filter(&self, url: &str, kind: UrlKind) -> Option<String> {
+        if self.is_allowed(url, kind) {
+            Some(url.to_string())
+        } else {
+            log::warn!("disallowed url: {}", url);
+            match &self.disallowed {
+                DisallowedUrl::Remove => None,
+                DisallowedUrl::Replace(replacement) => Some(replacement.to_string()),
+            }
+        }
+    }
+
+    fn is_image_data_allowed(&self, url: &str) -> bool {
+        let data = url
+            .trim()
+            .split_once(':')
+            .map(|(_, data)| data)
+            .unwrap_or("");
+        let mime_end = data
+            .find(|c: char| c == ';' || c == ',')
+            .unwrap_or(data.len());
+        let mime_type = data[..mime_end].trim().to_lowercase();
+        self.image_mime_types.contains(&mime_type)
+    }
+}
+
+/// the lowercased scheme of the url, `None` if the url is relative.
+/// Entities and the whitespace and control characters which browsers ignore are removed
+/// so obfuscated urls such as `java&#x09;script:` are detected.
+pub(crate) fn url_scheme(url: &str) -> Option<String> {
+    let url = html_escape::decode_html_entities(url);
+    let url: String = url
+        .chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .collect();
+    let end = url.find(|c: char| c == ':' || c == '/' || c == '?' || c == '#')?;
+    if url[end..].starts_with(':') {
+        Some(url[..end].to_lowercase())
+    } else {
+        None
+    }
+}
@@ -0,0 +1,110 @@
+use sauron::{html::node_list, *};
+use sauron_markdown::{parse, parse_with_options, Admonitions, MarkdownOptions};
+
+fn render(md: &str, admonitions: Admonitions) -> String {
+    let options = MarkdownOptions::default().admonitions(Some(admonitions));
+    node_list::<()>(parse_with_options(md, &options)).render_to_string()
+}
+
+#[test]
+fn github_note() {
+    let md = "> [!NOTE]\n> Useful information.";
+    let expected = "<div class=\"admonition note\">\
+<p class=\"admonition-title\">Note</p>\
+<p>Useful information.</p>\
+</div>";
+    assert_eq!(expected, render(md, Admonitions::new()));
+}
+
+#[test]
+fn github_custom_title() {
+    let md = "> [!WARNING] Read this first\n>\n> Critical content.";
+    let expected = "<div class=\"admonition warning\">\
+<p class=\"admonition-title\">Read this first</p>\
+<p>Critical content.</p>\
+</div>";
+    assert_eq!(expected, render(md, Admonitions::new()));
+}
+
+#[test]
+fn github_collapsed() {
+    let md = "> [!TIP]- Spoiler\n> Hidden content.";
+    let expected = "<details class=\"admonition tip\">\
+<summary class=\"admonition-title\">Spoiler</summary>\
+<p>Hidden content.</p>\
+</details>";
+    assert_eq!(expected, render(md, Admonitions::new()));
+}
+
+#[test]
+fn fenced_admonition() {
+    let md = "!!! warning \"Careful\"\n    first\n\n    second\n\nafter";
+    let expected = "<div class=\"admonition warning\">\
+<p class=\"admonition-title\">Careful</p>\
+<p>first</p>\
+<p>second</p>\
+</div>\
+<p>after</p>";
+    assert_eq!(expected, render(md, Admonitions::new()));
+}
+
+#[test]
+fn custom_kind_with_icon() {
+    let md = "!!! success\n    It worked.";
+    let admonitions = Admonitions::empty().kind("success", "Done", Some("✔"));
+    let expected = "<div class=\"admonition success\">\
+<p class=\"admonition-title\"><span class=\"admonition-icon\">✔</span>Done</p>\
+<p>It worked.</p>\
+</div>";
+    assert_eq!(expected, render(md, admonitions));
+}
+
+#[test]
+fn unregistered_kind_is_a_blockquote() {
+    let md = "> [!UNKNOWN]\n> text";
+    let plain = node_list::<()>(parse(md)).render_to_string();
+    assert_eq!(plain, render(md, Admonitions::new()));
+    assert!(plain.starts_with("<blockquote>"));
+}
+
+#[test]
+fn admonitions_are_opt_in() {
+    let md = "> [!NOTE]\n> text\n\n!!! note\n    text";
+    let html = node_list::<()>(parse(md)).render_to_string();
+    assert!(html.starts_with("<blockquote>"));
+    assert!(!html.contains("admonition"));
+}
+
+#[test]
+fn source_positions_in_fenced_admonition() {
+    let md = "!!! note\n    first\n\n    second";
+    let options = MarkdownOptions::default()
+        .admonitions(Some(Admonitions::new()))
+        .source_positions(true);
+    let html = node_list::<()>(parse_with_options(md, &options)).render_to_string();
+    let expected = "<div class=\"admonition note\" data-sourcepos=\"1:1-4:10\">\
+<p class=\"admonition-title\">Note</p>\
+<p data-sourcepos=\"2:5-2:9\">first</p>\
+<p data-sourcepos=\"4:5-4:10\">second</p>\
+</div>";
+    assert_eq!(expected, html);
+}
+
+#[test]
+fn references_and_rules_in_fenced_admonition() {
+    let md = "!!! note\n    see [docs]\n\n    ---\n\n    below\n\n[docs]: /docs";
+    let html = render(md, Admonitions::new());
+    assert!(
+        html.starts_with("<div class=\"admonition note\">"),
+        "{}",
+        html
+    );
+    assert!(
+        html.contains("<a href=\"/docs\" title=\"\">docs</a>"),
+        "{}",
+        html
+    );
+    // the rule is kept inside of the admonition
+    assert!(html.contains("</p><hr"), "{}", html);
+    assert!(html.ends_with("<p>below</p></div>"), "{}", html);
+}
@@ -0,0 +1,41 @@
+use sauron_markdown::sauron::*;
+use sauron_markdown::*;
+
+fn document(md: &str, options: &MarkdownOptions) -> Node<()> {
+    div([], parse_with_options(md, options))
+}
+
+#[test]
+fn keys_are_deterministic() {
+    let md = "same\n\nother\n\nsame";
+    let options = MarkdownOptions::new().block_keys(true);
+    let first = document(md, &options).render_to_string();
+    let second = document(md, &options).render_to_string();
+    assert_eq!(first, second);
+    assert_eq!(3, first.matches("key=").count());
+}
+
+#[test]
+fn fewer_patches_with_block_keys() {
+    let md: String = (0..20)
+        .map(|i| format!("paragraph number {}\n\n", i))
+        .collect();
+    let edited = format!("an inserted paragraph\n\n{}", md);
+
+    let plain = MarkdownOptions::new();
+    let old = document(&md, &plain);
+    let new = document(&edited, &plain);
+    let plain_patches = diff(&old, &new).len();
+
+    let keyed = MarkdownOptions::new().block_keys(true);
+    let old = document(&md, &keyed);
+    let new = document(&edited, &keyed);
+    let keyed_patches = diff(&old, &new).len();
+
+    assert!(
+        keyed_patches < plain_patches,
+        "keyed: {}, plain: {}",
+        keyed_patches,
+        plain_patches
+    );
+}
@@ -24,16 +24,75 @@ fn main(){
 
     let html = node.render_to_string();
     let expected = "\
-<code class=\"rust\">\
+<pre><code class=\"language-rust\">\
 fn main(){\
 \n    println!(\"this is real code block here\");\
 \n}\
-\n</code><pre></pre><code></code>        struct Foo {\
+\n</code></pre><pre><code>\
+\n        struct Foo {\
 \n            int bar;\
 \n            date baz;\
 \n            string quux;\
 \n          };\
+\n\
 \n          //somewhere in something\
-\n          Array    \n";
+\n          Array&lt;Foo&gt; foos;\
+\n    </code></pre>";
     assert_eq!(html, expected);
 }
+
+#[test]
+fn test_code_fence_processor() {
+    use sauron_markdown::{CodeFenceInfo, MarkdownParser};
+
+    let md = r#"
+```upper {.shout}
+hello world
+```
+
+```rust
+fn main(){}
+```"#;
+    let parser = MarkdownParser::new().add_code_fence_processor(
+        "upper",
+        |info: &CodeFenceInfo, code: &str| -> Option<Node<()>> {
+            assert_eq!(info.classes, vec!["shout"]);
+            Some(p([], [text(code.trim().to_uppercase())]))
+        },
+    );
+    let html = node_list(parser.parse(md)).render_to_string();
+    let expected = "<p>HELLO WORLD</p>\
+        <pre><code class=\"language-rust\">fn main(){}\n</code></pre>";
+    assert_eq!(expected, html);
+}
+
+#[test]
+fn test_line_numbers_and_highlighted_lines() {
+    let md = r#"```text {2} linenos title="notes.txt"
+one
+two
+```"#;
+    let html = node_list::<()>(parse(md)).render_to_string();
+    let expected = "<figure class=\"code-block\">\
+        <figcaption class=\"code-title\">notes.txt</figcaption>\
+        <pre><code class=\"language-text\">\
+        <span class=\"line\" data-line=\"1\"><span class=\"line-number\">1</span>one\n</span>\
+        <span class=\"line highlighted\" data-line=\"2\"><span class=\"line-number\">2</span>two\n</span>\
+        </code></pre></figure>";
+    assert_eq!(expected, html);
+}
+
+#[test]
+fn test_copy_button() {
+    use sauron_markdown::MarkdownParser;
+
+    let md = "```rust\nfn main(){}\n```\n\n    indented\n";
+    let parser = MarkdownParser::new().on_code_copy(|code: String| code);
+    let html = node_list(parser.parse(md)).render_to_string();
+    let expected = "<div class=\"code-container\">\
+        <pre><code class=\"language-rust\">fn main(){}\n</code></pre>\
+        <button class=\"copy-button\" type=\"button\">Copy</button>\
+        </div>\
+        <pre><code>indented\n</code></pre>";
+    assert_eq!(expected, html);
+}
@@ -0,0 +1,79 @@
+use sauron_markdown::sauron::{html::node_list, *};
+use sauron_markdown::*;
+
+#[test]
+fn front_matter_is_stripped() {
+    let md = r#"---
+title: Hello
+---
+# Content"#;
+    let view: Node<()> = node_list(parse(md));
+    assert_eq!("<h1>Content</h1>", view.render_to_string());
+
+    let (front_matter, _nodes): (Option<FrontMatter>, Vec<Node<()>>) =
+        parse_with_front_matter_map(md);
+    let front_matter = front_matter.expect("must have front matter");
+    assert_eq!(FrontMatterFormat::Yaml, front_matter.format);
+    assert_eq!("title: Hello\n", front_matter.raw);
+    #[cfg(feature = "front-matter-yaml")]
+    assert_eq!(Some("Hello"), front_matter.title());
+    #[cfg(not(feature = "front-matter-yaml"))]
+    assert_eq!(None, front_matter.values());
+}
+
+#[test]
+fn front_matter_disabled() {
+    let md = "+++\ntitle = \"Hello\"\n+++\n";
+    let options = MarkdownOptions::new().front_matter(false);
+    let (front_matter, nodes): (Option<FrontMatter>, Vec<Node<()>>) =
+        MarkdownParser::with_options(options).parse_with_front_matter_map(md);
+    assert_eq!(None, front_matter);
+    assert!(!nodes.is_empty());
+}
+
+#[cfg(feature = "front-matter-yaml")]
+#[test]
+fn yaml_front_matter() {
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Post {
+        title: String,
+        tags: Vec<String>,
+    }
+
+    let md = "---\ntitle: Hello\ntags: [rust, web]\n---\nbody";
+    let (post, nodes): (Option<Post>, Vec<Node<()>>) = parse_with_front_matter(md);
+    let expected = Post {
+        title: "Hello".to_string(),
+        tags: vec!["rust".to_string(), "web".to_string()],
+    };
+    assert_eq!(Some(expected), post);
+    assert_eq!("<p>body</p>", node_list(nodes).render_to_string());
+
+    let (front_matter, _nodes): (Option<FrontMatter>, Vec<Node<()>>) =
+        parse_with_front_matter_map(md);
+    let front_matter = front_matter.expect("must have front matter");
+    assert_eq!(Some("Hello"), front_matter.title());
+    assert_eq!(
+        Some(&FrontMatterValue::List(vec![
+            FrontMatterValue::String("rust".to_string()),
+            FrontMatterValue::String("web".to_string()),
+        ])),
+        front_matter.get("tags")
+    );
+}
+
+#[cfg(feature = "front-matter-toml")]
+#[test]
+fn toml_front_matter_title_fallback() {
+    let md = "+++\ntitle = \"From toml\"\ndraft = true\n+++\nno heading";
+    let options = MarkdownOptions::new().title_fallback_front_matter(true);
+    let (title, _nodes): (Option<String>, Vec<Node<()>>) =
+        MarkdownParser::with_options(options).parse_with_title(md);
+    assert_eq!(Some("From toml".to_string()), title);
+
+    let result: Result<(Option<Vec<String>>, Vec<Node<()>>), MarkdownError> =
+        try_parse_with_front_matter(md);
+    assert!(matches!(result, Err(MarkdownError::FrontMatter(_))));
+}
@@ -0,0 +1,33 @@
+#![cfg(feature = "highlight")]
+use sauron::{html::node_list, *};
+use sauron_markdown::{parse, parse_with_options, HighlightTheme, MarkdownOptions, TokenKind};
+
+#[test]
+fn highlight_rust() {
+    let md = "```rust\nlet x = 1; // one\n```";
+    let node: Node<()> = node_list(parse(md));
+    let expected = "<pre><code class=\"language-rust\">\
+<span class=\"tok-keyword\">let</span> x = <span class=\"tok-number\">1</span>; \
+<span class=\"tok-comment\">// one</span>\
+\n</code></pre>";
+    assert_eq!(expected, node.render_to_string());
+}
+
+#[test]
+fn unknown_language_is_plain_text() {
+    let md = "```brainfuck\n++[>+<-]\n```";
+    let options = MarkdownOptions::default().highlight(false);
+    let plain: Node<()> = node_list(parse_with_options(md, &options));
+    let node: Node<()> = node_list(parse(md));
+    assert_eq!(plain.render_to_string(), node.render_to_string());
+}
+
+#[test]
+fn highlight_with_theme() {
+    let md = "```python\nreturn None\n```";
+    let theme = HighlightTheme::empty().style(TokenKind::Keyword, "color:red");
+    let options = MarkdownOptions::default().highlight_theme(Some(theme));
+    let html = node_list::<()>(parse_with_options(md, &options)).render_to_string();
+    assert!(html.contains("<span style=\"color:red\">return</span>"));
+    assert!(!html.contains("tok-"));
+}
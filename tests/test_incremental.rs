@@ -0,0 +1,40 @@
+use sauron_markdown::sauron::{html::node_list, *};
+use sauron_markdown::*;
+
+fn render(nodes: Vec<Node<()>>) -> String {
+    node_list(nodes).render_to_string()
+}
+
+#[test]
+fn only_the_edited_block_is_parsed() {
+    let mut document = IncrementalDocument::new(MarkdownParser::new());
+    let md = "# Title\n\nfirst [link][home]\n\n- a\n- b\n\n[home]: https://example.com\n";
+    document.update(md);
+    assert_eq!(3, document.reparsed_blocks());
+
+    let edited = md.replace("- b", "- b\n- c");
+    let nodes = document.update(&edited);
+    assert_eq!(1, document.reparsed_blocks());
+
+    let mut fresh = IncrementalDocument::new(MarkdownParser::new());
+    assert_eq!(render(fresh.update(&edited)), render(nodes));
+}
+
+#[test]
+fn blocks_inside_unclosed_html_are_grouped() {
+    let mut document = IncrementalDocument::new(MarkdownParser::new());
+    let md = "<details>\n\n*hidden*\n\n</details>\n\nafter";
+    let nodes = document.update(md);
+    assert_eq!(2, document.reparsed_blocks());
+    assert_eq!(2, nodes.len());
+}
+
+#[test]
+fn footnotes_parse_the_whole_document() {
+    let mut document = IncrementalDocument::new(MarkdownParser::new());
+    let md = "note[^1]\n\n[^1]: the note";
+    document.update(md);
+    let nodes = document.update(md);
+    assert_eq!(2, document.reparsed_blocks());
+    assert_eq!(2, nodes.len());
+}
@@ -0,0 +1,100 @@
+use sauron_markdown::sauron::{html::node_list, *};
+use sauron_markdown::*;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Msg {
+    Navigate(String),
+}
+
+#[test]
+fn external_links_in_new_tab() {
+    let md = "[docs](/docs) and [site](https://example.com)";
+    let options = MarkdownOptions::new().external_links_new_tab(true);
+    let view: Node<()> = node_list(parse_with_options(md, &options));
+    let expected = "<p><a href=\"/docs\" title=\"\">docs</a> and \
+        <a href=\"https://example.com\" title=\"\" target=\"_blank\" rel=\"noopener\">site</a></p>";
+    assert_eq!(expected, view.render_to_string());
+}
+
+#[test]
+fn internal_link_click() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let md = "[docs](/docs \"Docs\"), [top](#top) and [site](https://example.com)";
+    let clicked = Rc::new(RefCell::new(vec![]));
+    let parser = MarkdownParser::new().on_link_click({
+        let clicked = Rc::clone(&clicked);
+        move |link: LinkInfo| {
+            clicked.borrow_mut().push(link.clone());
+            if link.url.starts_with('/') {
+                Some(Msg::Navigate(link.url))
+            } else {
+                None
+            }
+        }
+    });
+    let view: Node<Msg> = node_list(parser.parse(md));
+    // the event listeners are not rendered
+    let plain: Node<Msg> = node_list(parse(md));
+    assert_eq!(plain.render_to_string(), view.render_to_string());
+
+    let clicked = clicked.borrow();
+    assert_eq!(2, clicked.len());
+    assert_eq!(
+        LinkInfo {
+            url: "/docs".to_string(),
+            title: "Docs".to_string(),
+            is_internal: true,
+        },
+        clicked[0]
+    );
+    assert_eq!("#top", clicked[1].url);
+}
+
+#[test]
+fn no_click_handler_on_removed_urls() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    let md = "[docs](/docs) and [bad](javascript:alert(1))";
+    let options = MarkdownOptions::new().url_policy(Some(UrlPolicy::new().allow_relative(false)));
+    let clicked = Rc::new(Cell::new(0));
+    let parser = MarkdownParser::with_options(options).on_link_click({
+        let clicked = Rc::clone(&clicked);
+        move |link: LinkInfo| {
+            clicked.set(clicked.get() + 1);
+            Some(Msg::Navigate(link.url))
+        }
+    });
+    let view: Node<Msg> = node_list(parser.parse(md));
+    assert_eq!(0, clicked.get());
+    assert_eq!(
+        "<p><a title=\"\">docs</a> and <a title=\"\">bad</a></p>",
+        view.render_to_string()
+    );
+}
+
+#[test]
+fn no_click_handler_on_replaced_urls() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    let md = "[bad](javascript:alert(1))";
+    let url_policy = UrlPolicy::new().disallowed(DisallowedUrl::Replace("#".to_string()));
+    let options = MarkdownOptions::new().url_policy(Some(url_policy));
+    let clicked = Rc::new(Cell::new(0));
+    let parser = MarkdownParser::with_options(options).on_link_click({
+        let clicked = Rc::clone(&clicked);
+        move |link: LinkInfo| {
+            clicked.set(clicked.get() + 1);
+            Some(Msg::Navigate(link.url))
+        }
+    });
+    let view: Node<Msg> = node_list(parser.parse(md));
+    assert_eq!(0, clicked.get());
+    assert_eq!(
+        "<p><a href=\"#\" title=\"\">bad</a></p>",
+        view.render_to_string()
+    );
+}
@@ -13,11 +13,29 @@ fn test_inline_htmls() {
     </footer>
 </article>"#;
 
+    let expected = "<article class=\"side-to-side\"><div>\n        This is div content1\n    </div><footer>\n        This is footer\n    </footer></article>";
+
     let view: Node<()> = node_list(parse(md));
 
     let buffer = view.render_to_string();
     println!("view: {}", buffer);
-    assert_eq!(md, buffer);
+    assert_eq!(expected, buffer);
+}
+
+#[cfg(feature = "parse-html")]
+#[test]
+fn test_inline_html_merged_with_markdown() {
+    let md = r#"This is <b>bold *and emphasized*</b> text
+
+<details>
+
+Some **markdown** inside
+
+</details>"#;
+    let view: Node<()> = node_list(parse(md));
+    let expected = "<p>This is <b>bold <em>and emphasized</em></b> text</p>\
+        <details><p>Some <strong>markdown</strong> inside</p></details>";
+    assert_eq!(expected, view.render_to_string());
 }
 
 #[test]
@@ -30,7 +48,7 @@ fn source_code() {
 ```
         "#;
     let expected =
-        "<code class=\"rust\">    fn main(){\n        println!(\"Hello world!\");\n    }\n</code>";
+        "<pre><code class=\"language-rust\">    fn main(){\n        println!(\"Hello world!\");\n    }\n</code></pre>";
     let view: Node<()> = node_list(parse(md));
 
     let mut buffer = String::new();
@@ -72,17 +90,54 @@ Duplicated footnote reference[^second].
 [^second]: Footnote text.
         "#;
 
-    let expected = "<h3><a href=\"https://github.com/markdown-it/markdown-it-footnote\" title=\"\">Footnotes</a></h3><p>Footnote
- 1 link<a href=\"first\"></a>.</p><p>Footnote 2 link<a href=\"second\"></a>.</p><p>Inline footnote^[Text of inline
-footnote] definition.</p><p>Duplicated footnote reference<a href=\"second\"></a>.</p><footer class=\"footnote-defin
-ition\" id=\"first\"><p>Footnote <strong>can have markup</strong></p></footer><code>and multiple paragraphs.\n</cod
-e><footer class=\"footnote-definition\" id=\"second\"><p>Footnote text.</p></footer>";
+    let expected = "<h3><a href=\"https://github.com/markdown-it/markdown-it-footnote\" title=\"\">Footnotes</a></h3>\
+        <p>Footnote 1 link<sup><a href=\"#fn-first\" id=\"fnref-first\">1</a></sup>.</p>\
+        <p>Footnote 2 link<sup><a href=\"#fn-second\" id=\"fnref-second\">2</a></sup>.</p>\
+        <p>Inline footnote^[Text of inline footnote] definition.</p>\
+        <p>Duplicated footnote reference<sup><a href=\"#fn-second\" id=\"fnref-second-2\">2</a></sup>.</p>\
+        <pre><code>and multiple paragraphs.\n</code></pre>\
+        <section class=\"footnotes\"><ol>\
+            <li id=\"fn-first\"><p>Footnote <strong>can have markup</strong></p>\
+                <a href=\"#fnref-first\" class=\"footnote-backref\">↩</a>\
+            </li>\
+            <li id=\"fn-second\"><p>Footnote text.</p>\
+                <a href=\"#fnref-second\" class=\"footnote-backref\">↩</a>\
+                <a href=\"#fnref-second-2\" class=\"footnote-backref\">↩<sup>2</sup></a>\
+            </li>\
+        </ol></section>";
     let view: Node<()> = node_list(parse(md));
     let buffer = view.render_to_string();
     println!("view: {:?}", buffer);
     assert_eq!(buffer, expected);
 }
 
+#[test]
+fn footnotes_not_linked() {
+    let md = r#"
+Referenced[^used] and missing[^missing].
+
+[^used]: Used.
+
+[^unused]: Never referenced.
+        "#;
+
+    let (nodes, report) = parse_with_footnotes::<()>(md);
+    assert_eq!(
+        report,
+        FootnoteReport {
+            undefined: vec!["missing".to_string()],
+            unreferenced: vec!["unused".to_string()],
+        }
+    );
+    assert!(!report.is_empty());
+    let buffer = node_list(nodes).render_to_string();
+    assert!(buffer.contains("<li id=\"fn-used\">"));
+    assert!(!buffer.contains("Never referenced"));
+
+    let (_nodes, report) = parse_with_footnotes::<()>("A note[^a].\n\n[^a]: Note.");
+    assert!(report.is_empty());
+}
+
 #[test]
 fn test_md_with_html() {
     let md = r#"
@@ -184,35 +239,38 @@ fn test_md_tables() {
 }
 "#;
     let view: Node<()> = node_list(parse(md));
-    let expected = "<p>\
-    <h2>Tables</h2>\
+    let expected = "<h2>Tables</h2>\
     <table>\
-        <tr>\
-            <th class=\"text-left\">Option</th>\
-            <th class=\"text-right\">Description</th>\
-        </tr>\
-        <tr>\
-            <td class=\"text-left\">data</td>\
-            <td class=\"text-right\">path to data files to supply the data that will be passed into templates.</td>\
-        </tr>\
-        <tr>\
-            <td class=\"text-left\">engine</td>\
-            <td class=\"text-right\">engine to be used for processing templates. Handlebars is the default.</td>\
-        </tr>\
-        <tr>\
-            <td class=\"text-left\">ext</td>\
-            <td class=\"text-right\">extension to be used for dest files.</td>\
-        </tr>\
-        <tr>\
-            <td class=\"text-left\">}</td>\
-            <td class=\"text-right\"></td>\
-        </tr>\
-    </table>\
-</p>";
+        <thead>\
+            <tr>\
+                <th class=\"text-left\">Option</th>\
+                <th class=\"text-right\">Description</th>\
+            </tr>\
+        </thead>\
+        <tbody>\
+            <tr>\
+                <td class=\"text-left\">data</td>\
+                <td class=\"text-right\">path to data files to supply the data that will be passed into templates.</td>\
+            </tr>\
+            <tr>\
+                <td class=\"text-left\">engine</td>\
+                <td class=\"text-right\">engine to be used for processing templates. Handlebars is the default.</td>\
+            </tr>\
+            <tr>\
+                <td class=\"text-left\">ext</td>\
+                <td class=\"text-right\">extension to be used for dest files.</td>\
+            </tr>\
+            <tr>\
+                <td class=\"text-left\">}</td>\
+                <td class=\"text-right\"></td>\
+            </tr>\
+        </tbody>\
+    </table>";
 
     assert_eq!(expected, view.render_to_string());
 }
 
+#[cfg(feature = "svgbob")]
 #[test]
 fn test_md_with_svgbob_processor() {
     let md = r#"
@@ -228,7 +286,185 @@ fn test_md_with_svgbob_processor() {
     let html = node.render_to_string();
     println!("html: {}", html);
     dbg!(&html);
-    let expected = r#"<p><pre><code>This is <b>Markdown</b> with some <i>funky</i> __examples__.
-</code></pre><pre><code class="bob"><svg xmlns="http://www.w3.org/2000/svg" width="248" height="64"><style>line, path, circle,rect,polygon{stroke:black;stroke-width:2;stroke-opacity:1;fill-opacity:1;stroke-linecap:round;stroke-linejoin:miter;}text{font-family:monospace;font-size:14px;}rect.backdrop{stroke:none;fill:white;}.broken{stroke-dasharray:8;}.filled{fill:black;}.bg_filled{fill:white;}.nofill{fill:white;}.end_marked_arrow{marker-end:url(#arrow);}.start_marked_arrow{marker-start:url(#arrow);}.end_marked_diamond{marker-end:url(#diamond);}.start_marked_diamond{marker-start:url(#diamond);}.end_marked_circle{marker-end:url(#circle);}.start_marked_circle{marker-start:url(#circle);}.end_marked_open_circle{marker-end:url(#open_circle);}.start_marked_open_circle{marker-start:url(#open_circle);}.end_marked_big_open_circle{marker-end:url(#big_open_circle);}.start_marked_big_open_circle{marker-start:url(#big_open_circle);}</style><defs><marker id="arrow" viewBox="-2 -2 8 8" refX="4" refY="2" markerWidth="7" markerHeight="7" orient="auto-start-reverse"><polygon points="0,0 0,4 4,2 0,0"></polygon></marker><marker id="diamond" viewBox="-2 -2 8 8" refX="4" refY="2" markerWidth="7" markerHeight="7" orient="auto-start-reverse"><polygon points="0,2 2,0 4,2 2,4 0,2"></polygon></marker><marker id="circle" viewBox="0 0 8 8" refX="4" refY="4" markerWidth="7" markerHeight="7" orient="auto-start-reverse"><circle cx="4" cy="4" r="2" class="filled"></circle></marker><marker id="open_circle" viewBox="0 0 8 8" refX="4" refY="4" markerWidth="7" markerHeight="7" orient="auto-start-reverse"><circle cx="4" cy="4" r="2" class="bg_filled"></circle></marker><marker id="big_open_circle" viewBox="0 0 8 8" refX="4" refY="4" markerWidth="7" markerHeight="7" orient="auto-start-reverse"><circle cx="4" cy="4" r="3" class="bg_filled"></circle></marker></defs><rect class="backdrop" x="0" y="0" width="248" height="64"></rect><rect x="52" y="8" width="56" height="32" class="solid nofill" rx="4"></rect><text x="66" y="28" >bob</text><rect x="172" y="8" width="64" height="32" class="solid nofill" rx="0"></rect><text x="186" y="28" >alice</text><circle cx="124" cy="24" r="3" class="filled"></circle><g><line x1="128" y1="24" x2="152" y2="24" class="solid"></line><polygon points="152,20 160,24 152,28" class="filled"></polygon></g></svg></code></pre></p>"#;
+    let expected = r#"<pre><code>This is &lt;b&gt;Markdown&lt;/b&gt; with some &lt;i&gt;funky&lt;/i&gt; __examples__.
+</code></pre><pre><code class="bob"><svg xmlns="http://www.w3.org/2000/svg" width="248" height="64"><style>line, path, circle,rect,polygon{stroke:black;stroke-width:2;stroke-opacity:1;fill-opacity:1;stroke-linecap:round;stroke-linejoin:miter;}text{font-family:monospace;font-size:14px;}rect.backdrop{stroke:none;fill:white;}.broken{stroke-dasharray:8;}.filled{fill:black;}.bg_filled{fill:white;}.nofill{fill:white;}.end_marked_arrow{marker-end:url(#arrow);}.start_marked_arrow{marker-start:url(#arrow);}.end_marked_diamond{marker-end:url(#diamond);}.start_marked_diamond{marker-start:url(#diamond);}.end_marked_circle{marker-end:url(#circle);}.start_marked_circle{marker-start:url(#circle);}.end_marked_open_circle{marker-end:url(#open_circle);}.start_marked_open_circle{marker-start:url(#open_circle);}.end_marked_big_open_circle{marker-end:url(#big_open_circle);}.start_marked_big_open_circle{marker-start:url(#big_open_circle);}</style><defs><marker id="arrow" viewBox="-2 -2 8 8" refX="4" refY="2" markerWidth="7" markerHeight="7" orient="auto-start-reverse"><polygon points="0,0 0,4 4,2 0,0"></polygon></marker><marker id="diamond" viewBox="-2 -2 8 8" refX="4" refY="2" markerWidth="7" markerHeight="7" orient="auto-start-reverse"><polygon points="0,2 2,0 4,2 2,4 0,2"></polygon></marker><marker id="circle" viewBox="0 0 8 8" refX="4" refY="4" markerWidth="7" markerHeight="7" orient="auto-start-reverse"><circle cx="4" cy="4" r="2" class="filled"></circle></marker><marker id="open_circle" viewBox="0 0 8 8" refX="4" refY="4" markerWidth="7" markerHeight="7" orient="auto-start-reverse"><circle cx="4" cy="4" r="2" class="bg_filled"></circle></marker><marker id="big_open_circle" viewBox="0 0 8 8" refX="4" refY="4" markerWidth="7" markerHeight="7" orient="auto-start-reverse"><circle cx="4" cy="4" r="3" class="bg_filled"></circle></marker></defs><rect class="backdrop" x="0" y="0" width="248" height="64"></rect><rect x="52" y="8" width="56" height="32" class="solid nofill" rx="4"></rect><text x="66" y="28" >bob</text><rect x="172" y="8" width="64" height="32" class="solid nofill" rx="0"></rect><text x="186" y="28" >alice</text><circle cx="124" cy="24" r="3" class="filled"></circle><g><line x1="128" y1="24" x2="152" y2="24" class="solid"></line><polygon points="152,20 160,24 152,28" class="filled"></polygon></g></svg></code></pre>"#;
     assert_eq!(expected, html);
 }
+
+#[test]
+fn test_try_parse() {
+    let md = r#"
+# Title
+
+Some *emphasized* text"#;
+    let nodes: Vec<Node<()>> = try_parse(md).expect("must parse");
+    let expected = "<h1>Title</h1><p>Some <em>emphasized</em> text</p>";
+    assert_eq!(expected, node_list(nodes).render_to_string());
+
+    let (title, _nodes): (Option<String>, Vec<Node<()>>) =
+        try_parse_with_title(md).expect("must parse");
+    assert_eq!(Some("Title".to_string()), title);
+}
+
+#[test]
+fn test_restricted_options() {
+    let md = r#"~~strike~~ and <b>bold</b>"#;
+    let options = MarkdownOptions::commonmark().allow_html(false);
+    let view: Node<()> = node_list(parse_with_options(md, &options));
+    let expected = "<p>~~strike~~ and &lt;b&gt;bold&lt;/b&gt;</p>";
+    assert_eq!(expected, view.render_to_string());
+
+    let options = MarkdownOptions::commonmark().strikethrough(true);
+    let view: Node<()> = node_list(parse_with_options("~~strike~~", &options));
+    assert_eq!("<p><s>strike</s></p>", view.render_to_string());
+}
+
+#[test]
+fn test_code_block_info_string() {
+    let md = r#"
+```rust,ignore {.numberLines}
+let x = 1;
+```"#;
+    let options = MarkdownOptions::new().code_class_prefix("lang-");
+    let view: Node<()> = node_list(parse_with_options(md, &options));
+    let expected = "<pre><code class=\"lang-rust numberLines\">let x = 1;\n</code></pre>";
+    assert_eq!(expected, view.render_to_string());
+}
+
+#[test]
+fn test_node_transforms() {
+    let md = r#"
+## Heading
+
+![](img.jpeg)"#;
+    let parser = MarkdownParser::new()
+        .add_node_transform(|node: Node<()>| {
+            if node.tag() == Some(&"img") {
+                None
+            } else {
+                Some(node)
+            }
+        })
+        .add_node_transform(|node: Node<()>| {
+            if node.tag() == Some(&"h2") {
+                Some(header([], [node]))
+            } else {
+                Some(node)
+            }
+        });
+    let view: Node<()> = node_list(parser.parse(md));
+    let expected = "<header><h2>Heading</h2></header><p></p>";
+    assert_eq!(expected, view.render_to_string());
+}
+
+#[test]
+fn test_md_tables_alignment_style() {
+    let md = r#"
+| Name | Count |
+|:----:| ----- |
+| a    | 1     |"#;
+    let options = MarkdownOptions::new().table_alignment(TableAlignment::Style);
+    let view: Node<()> = node_list(parse_with_options(md, &options));
+    let expected = "<table>\
+        <thead><tr><th style=\"text-align:center\">Name</th><th>Count</th></tr></thead>\
+        <tbody><tr><td style=\"text-align:center\">a</td><td>1</td></tr></tbody>\
+    </table>";
+    assert_eq!(expected, view.render_to_string());
+}
+
+#[test]
+fn test_md_table_without_body() {
+    let md = "| Name | Count |\n| ---- | ----- |";
+    let view: Node<()> = node_list(parse(md));
+    let expected = "<table>\
+        <thead><tr><th>Name</th><th>Count</th></tr></thead>\
+    </table>";
+    assert_eq!(expected, view.render_to_string());
+}
+
+#[test]
+fn test_heading_ids() {
+    let md = r#"
+# Hello, World!
+## Hello, World!
+## Custom `id` {#my-id}"#;
+    let options = MarkdownOptions::new().heading_ids(true);
+    let view: Node<()> = node_list(parse_with_options(md, &options));
+    let expected = "<h1 id=\"hello-world\">Hello, World!</h1>\
+        <h2 id=\"hello-world-1\">Hello, World!</h2>\
+        <h2 id=\"my-id\">Custom <code>id</code></h2>";
+    assert_eq!(expected, view.render_to_string());
+}
+
+#[test]
+fn test_heading_permalink_and_slugger() {
+    let md = "## Getting Started";
+    let options = MarkdownOptions::new()
+        .heading_ids(true)
+        .heading_permalink(Some("#"));
+    let parser = MarkdownParser::with_options(options)
+        .slugger(|text: &str| text.to_lowercase().replace(' ', "_"));
+    let view: Node<()> = node_list(parser.parse(md));
+    let expected = "<h2 id=\"getting_started\">Getting Started\
+        <a class=\"anchor\" href=\"#getting_started\">#</a></h2>";
+    assert_eq!(expected, view.render_to_string());
+}
+
+#[test]
+fn test_parse_with_toc() {
+    let md = r#"
+# Guide
+## Install
+### From source
+## Usage `cli`"#;
+    let (toc, _nodes): (Vec<Toc>, Vec<Node<()>>) = parse_with_toc(md);
+    assert_eq!(toc.len(), 1);
+    assert_eq!(toc[0].id, "guide");
+    let children: Vec<_> = toc[0]
+        .children
+        .iter()
+        .map(|entry| (entry.level, entry.text.as_str(), entry.id.as_str()))
+        .collect();
+    assert_eq!(
+        children,
+        vec![(2, "Install", "install"), (2, "Usage cli", "usage-cli")]
+    );
+    assert_eq!(toc[0].children[0].children[0].id, "from-source");
+}
+
+#[test]
+fn test_toc_placeholder() {
+    let md = r#"
+[TOC]
+
+# Intro
+## Details"#;
+    let options = MarkdownOptions::new().toc_placeholder(true);
+    let view: Node<()> = node_list(parse_with_options(md, &options));
+    let expected = "<nav class=\"toc\"><ul><li><a href=\"#intro\">Intro</a>\
+        <ul><li><a href=\"#details\">Details</a></li></ul></li></ul></nav>\
+        <h1 id=\"intro\">Intro</h1><h2 id=\"details\">Details</h2>";
+    assert_eq!(expected, view.render_to_string());
+}
+
+#[test]
+fn test_formatted_title() {
+    let md = "# Using `code` in *[links](https://example.com)*\n\nbody";
+    let (title, _nodes): (Option<String>, Vec<Node<()>>) = parse_with_title(md);
+    assert_eq!(Some("Using code in links".to_string()), title);
+}
+
+#[test]
+fn test_title_fallback_and_strip() {
+    let md = "## Sub *title*\n\nbody";
+    let options = MarkdownOptions::new()
+        .title_fallback_h2(true)
+        .strip_title(true);
+    let (title, nodes): (Option<String>, Vec<Node<()>>) =
+        MarkdownParser::with_options(options).parse_with_title(md);
+    assert_eq!(Some("Sub title".to_string()), title);
+    assert_eq!("<p>body</p>", node_list(nodes).render_to_string());
+
+    let (title, _nodes): (Option<String>, Vec<Node<()>>) = parse_with_title(md);
+    assert_eq!(None, title);
+}
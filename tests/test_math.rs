@@ -0,0 +1,93 @@
+use sauron::{html::node_list, *};
+use sauron_markdown::{parse, CodeFenceInfo, MarkdownOptions, MarkdownParser};
+
+fn options() -> MarkdownOptions {
+    #[cfg(feature = "mathml")]
+    return MarkdownOptions::default().math(true).mathml(false);
+    #[cfg(not(feature = "mathml"))]
+    MarkdownOptions::default().math(true)
+}
+
+fn render(md: &str, options: MarkdownOptions) -> String {
+    let parser = MarkdownParser::with_options(options);
+    node_list::<()>(parser.parse(md)).render_to_string()
+}
+
+#[test]
+fn inline_math() {
+    let md = r"The area is $\pi r^2$.";
+    let expected = "<p>The area is <span class=\"math inline\">\\pi r^2</span>.</p>";
+    assert_eq!(expected, render(md, options()));
+}
+
+#[test]
+fn display_math() {
+    let md = r"$$
+\int_0^1 x^2 \, dx = \frac{1}{3}
+$$";
+    let expected = "<div class=\"math display\">\\int_0^1 x^2 \\, dx = \\frac{1}{3}</div>";
+    assert_eq!(expected, render(md, options()));
+}
+
+#[test]
+fn display_math_in_paragraph() {
+    let md = "where $$x = 1$$ holds";
+    let expected = "<p>where <span class=\"math display\">x = 1</span> holds</p>";
+    assert_eq!(expected, render(md, options()));
+}
+
+#[test]
+fn math_fence() {
+    let md = "```math\nE = mc^2\n```";
+    let expected = "<div class=\"math display\">E = mc^2</div>";
+    assert_eq!(expected, render(md, options()));
+}
+
+#[test]
+fn custom_math_processor() {
+    let parser = MarkdownParser::<()>::new()
+        .add_code_fence_processor("math", |_info: &CodeFenceInfo, tex: &str| {
+            Some(code([], [text(tex)]))
+        })
+        .options(options());
+    let html = node_list(parser.parse("$x$")).render_to_string();
+    assert_eq!("<p><code>x</code></p>", html);
+}
+
+#[test]
+fn custom_math_processor_without_output() {
+    let parser = MarkdownParser::<()>::new()
+        .add_code_fence_processor("math", |_info: &CodeFenceInfo, _tex: &str| None)
+        .options(options());
+    let html = node_list(parser.parse("$x$")).render_to_string();
+    assert_eq!("<p><span class=\"math inline\">x</span></p>", html);
+}
+
+#[test]
+fn math_fence_with_flags_is_not_inline_math() {
+    let md = "```math inline\nx\n```";
+    let parser = MarkdownParser::with_options(options()).on_code_copy(|code: String| code);
+    let html = node_list(parser.parse(md)).render_to_string();
+    let expected = "<div class=\"code-container\">\
+        <span class=\"math inline\">x</span>\
+        <button class=\"copy-button\" type=\"button\">Copy</button>\
+        </div>";
+    assert_eq!(expected, html);
+}
+
+#[test]
+fn math_is_opt_in() {
+    let md = "costs $5, $x$ and\n\n```math\nx\n```";
+    let plain = node_list::<()>(parse(md)).render_to_string();
+    assert_eq!(plain, render(md, MarkdownOptions::default()));
+    assert!(plain.contains("$x$"));
+}
+
+#[cfg(feature = "mathml")]
+#[test]
+fn mathml() {
+    let md = "$x^2$";
+    let html = render(md, MarkdownOptions::default().math(true));
+    assert!(html.contains("<msup><mi>x</mi><mn>2</mn></msup>"));
+    assert!(html.contains("<annotation encoding=\"application/x-tex\">x^2</annotation>"));
+}
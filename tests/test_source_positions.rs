@@ -0,0 +1,49 @@
+use sauron_markdown::sauron::{html::node_list, *};
+use sauron_markdown::*;
+
+#[test]
+fn data_sourcepos() {
+    let md = "# Title\n\n- one\n- two\n\n```rust\nfn main() {}\n```\n";
+    let options = MarkdownOptions::new().source_positions(true);
+    let view: Node<()> = node_list(parse_with_options(md, &options));
+    let expected = "<h1 data-sourcepos=\"1:1-1:7\">Title</h1>\
+        <ul data-sourcepos=\"3:1-4:5\">\
+            <li data-sourcepos=\"3:1-3:5\">one</li>\
+            <li data-sourcepos=\"4:1-4:5\">two</li>\
+        </ul>\
+        <pre data-sourcepos=\"6:1-8:3\"><code class=\"language-rust\">fn main() {}\n</code></pre>";
+    assert_eq!(expected, view.render_to_string());
+}
+
+#[test]
+fn spans_of_top_level_nodes() {
+    let md = "---\ntitle: x\n---\nfirst\n\n---\n\n> quote\n\nnote[^1]\n\n[^1]: the note";
+    let (nodes, spans): (Vec<Node<()>>, _) = parse_with_spans(md);
+    assert_eq!(nodes.len(), spans.len());
+    let texts: Vec<Option<&str>> = spans
+        .iter()
+        .map(|span| span.clone().map(|span| &md[span]))
+        .collect();
+    assert_eq!(
+        texts,
+        vec![
+            Some("first"),
+            Some("---"),
+            Some("> quote"),
+            Some("note[^1]"),
+            None
+        ]
+    );
+}
+
+#[test]
+fn heading_ids_with_sourcepos() {
+    let md = "text\n\n## Sub {#sub}";
+    let options = MarkdownOptions::new()
+        .heading_ids(true)
+        .source_positions(true);
+    let view: Node<()> = node_list(parse_with_options(md, &options));
+    let expected = "<p data-sourcepos=\"1:1-1:4\">text</p>\
+        <h2 id=\"sub\" data-sourcepos=\"3:1-3:14\">Sub</h2>";
+    assert_eq!(expected, view.render_to_string());
+}
@@ -0,0 +1,41 @@
+use sauron_markdown::pulldown_cmark::{Event, Options, Parser};
+use sauron_markdown::sauron::{html::node_list, *};
+use sauron_markdown::*;
+
+#[derive(Debug, Clone)]
+enum Msg {
+    Toggle(TaskToggle),
+}
+
+#[test]
+fn checkboxes_with_toggle() {
+    let md = "- [ ] write docs\n- [x] write tests";
+    let parser = MarkdownParser::new().on_task_toggle(Msg::Toggle);
+    let view: Node<Msg> = node_list(parser.parse(md));
+    // the event listeners are not rendered
+    let plain: Node<Msg> = node_list(parse(md));
+    assert_eq!(plain.render_to_string(), view.render_to_string());
+}
+
+#[test]
+fn toggle_the_source() {
+    let md = "# Todo\n\n- [ ] write docs\n- [x] write tests\n";
+    let ranges: Vec<_> = Parser::new_ext(md, Options::ENABLE_TASKLISTS)
+        .into_offset_iter()
+        .filter(|(ev, _)| matches!(ev, Event::TaskListMarker(_)))
+        .map(|(_, range)| range)
+        .collect();
+    let first = TaskToggle {
+        index: 0,
+        range: ranges[0].clone(),
+        checked: true,
+    };
+    let second = TaskToggle {
+        index: 1,
+        range: ranges[1].clone(),
+        checked: false,
+    };
+    let md = first.apply(md).expect("must toggle");
+    let md = second.apply(&md).expect("must toggle");
+    assert_eq!("# Todo\n\n- [x] write docs\n- [ ] write tests\n", md);
+}
@@ -0,0 +1,52 @@
+use sauron::{html::node_list, *};
+use sauron_markdown::{parse_with_options, DisallowedUrl, MarkdownOptions, UrlPolicy};
+
+#[test]
+fn javascript_link() {
+    let md = r#"[you](javascript:alert('xss'))"#;
+    let view: Node<()> = node_list(sauron_markdown::parse(md));
+    let expected = r#"<p><a title="">you</a></p>"#;
+    assert_eq!(expected, view.render_to_string());
+}
+
+#[test]
+fn obfuscated_javascript_link() {
+    let md = r#"
+[mixed case](JaVaScRiPt:alert(1))
+
+[entity](&#106;avascript:alert(1))
+
+[vbscript](vbscript:msgbox(1))"#;
+    let view: Node<()> = node_list(sauron_markdown::parse(md));
+    let expected = r#"<p><a title="">mixed case</a></p><p><a title="">entity</a></p><p><a title="">vbscript</a></p>"#;
+    assert_eq!(expected, view.render_to_string());
+}
+
+#[test]
+fn data_urls() {
+    let md = r#"
+[link](data:text/html;base64,PHNjcmlwdD4=)
+
+![png](data:image/png;base64,iVBORw0KGgo=)
+
+![svg](data:image/svg+xml;base64,PHN2Zz4=)"#;
+    let view: Node<()> = node_list(sauron_markdown::parse(md));
+    let expected = r#"<p><a title="">link</a></p><p><img src="data:image/png;base64,iVBORw0KGgo=" title=""/></p><p><img title=""/></p>"#;
+    assert_eq!(expected, view.render_to_string());
+}
+
+#[test]
+fn relative_and_replaced_urls() {
+    let md = r#"[docs](../docs/index.html) [bad](javascript:alert(1))"#;
+    let view: Node<()> = node_list(sauron_markdown::parse(md));
+    let expected = r#"<p><a href="../docs/index.html" title="">docs</a> <a title="">bad</a></p>"#;
+    assert_eq!(expected, view.render_to_string());
+
+    let policy = UrlPolicy::new()
+        .allow_relative(false)
+        .disallowed(DisallowedUrl::Replace("#".to_string()));
+    let options = MarkdownOptions::new().url_policy(Some(policy));
+    let view: Node<()> = node_list(parse_with_options(md, &options));
+    let expected = r##"<p><a href="#" title="">docs</a> <a href="#" title="">bad</a></p>"##;
+    assert_eq!(expected, view.render_to_string());
+}
@@ -12,10 +12,9 @@ fn anchor() {
     println!("view: {}", buffer);
 
     let expected = r#"<p>
-    <em>
-        <a rel="noopener noreferrer"></a>
-        you
-    </em>
+    <a rel="noopener noreferrer">
+        <em>you</em>
+    </a>
 </p>"#;
     assert_eq!(expected, buffer);
 }
@@ -34,12 +33,51 @@ fn blockqupte_xss() {
     let expected = r#"<blockquote>
     <p>
         hello
-        <!--separator-->
-        href="javascript:alert('xss')"&gt;
-        <a rel="noopener noreferrer"></a>
-        <em>you</em>
+        <a rel="noopener noreferrer">
+            <em>you</em>
+        </a>
     </p>
 </blockquote>"#;
 
     assert_eq!(expected, buffer);
 }
+
+#[test]
+fn script_and_disallowed_tags() {
+    let md = r#"<div onclick="alert(1)"><script>alert('xss')</script><font color="red">text</font><img src="javascript:alert(1)" alt="x"/></div>"#;
+    let view: Node<()> = node_list(sauron_markdown::parse(md));
+    let expected = r#"<div>text<img alt="x"/></div>"#;
+    assert_eq!(expected, view.render_to_string());
+}
+
+#[test]
+fn custom_policy() {
+    use sauron_markdown::{parse_with_options, MarkdownOptions, SanitizePolicy};
+
+    let md = r#"<a href="ftp://example.com/file" rel="me">file</a>"#;
+    let policy = SanitizePolicy::new()
+        .allow_url_schemes(["ftp"])
+        .allow_attributes("a", ["rel"])
+        .link_rel(None::<String>);
+    let options = MarkdownOptions::new().sanitize_policy(Some(policy));
+    let view: Node<()> = node_list(parse_with_options(md, &options));
+    let expected = r#"<p><a href="ftp://example.com/file" rel="me">file</a></p>"#;
+    assert_eq!(expected, view.render_to_string());
+}
+
+#[test]
+fn malformed_html_is_not_passed_through() {
+    for md in &[
+        r#"<div onclick="alert(1)>text</div>"#,
+        r#"<img src=x onerror="alert(1)//"#,
+        r#"<p><a href="javascript:alert(1)" onmouseover=alert(1)>x</p></a></b>"#,
+    ] {
+        let html = node_list::<()>(sauron_markdown::parse(md)).render_to_string();
+        // the html which is not sanitized is escaped, so it is not in the tags
+        for tag in html.split('<').skip(1) {
+            let tag = &tag[..tag.find('>').unwrap_or(tag.len())];
+            assert!(!tag.contains(" on"), "{}", html);
+            assert!(!tag.contains("javascript:"), "{}", html);
+        }
+    }
+}